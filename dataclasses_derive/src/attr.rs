@@ -0,0 +1,1029 @@
+use inflector::Inflector;
+use proc_macro2::Span;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    Attribute, Error, Expr, Ident, Index, Item, Lit, LitStr, Member, Meta, NestedMeta, Path,
+    Result, Token, Type,
+};
+
+/// Every option accepted by the struct-level attribute, for diagnostics.
+const STRUCT_OPTIONS: &[&str] = &[
+    "iter_fields",
+    "schema",
+    "borrow",
+    "frozen",
+    "freeze_after",
+    "eq",
+    "eq_with",
+    "accessors",
+    "rename_all",
+    "builder",
+    "const_default",
+    "validate",
+    "option_ctor",
+    "hash",
+    "ref_view",
+    "collection_helpers",
+    "as_dict",
+    "as_vec",
+    "debug",
+    "boxed_ctor",
+    "reset",
+    "getter_prefix",
+    "default_all_optional",
+    "eq_order",
+    "compare_by",
+    "option_helpers",
+    "from_str",
+    "hash_by",
+    "order",
+    "bounded",
+    "from_field",
+    "equivalent",
+    "default_derive",
+    "freeze",
+    "dynamic",
+    "display",
+    "with",
+    "full",
+    "namespace",
+    "private_new",
+    "extends",
+    "try_into_tuple",
+    "total_float_eq",
+    "builder_setter_prefix",
+    "default_fn",
+    "const_new",
+    "modified",
+    "init",
+    "repr",
+    "kw_only",
+    "with_field",
+];
+
+/// The struct-level options that also apply to enums.
+const ENUM_OPTIONS: &[&str] = &["eq"];
+
+/// Every option accepted by a variant-level attribute, for diagnostics.
+const VARIANT_OPTIONS: &[&str] = &["default"];
+
+/// Every option accepted by a field-level attribute, for diagnostics.
+const FIELD_OPTIONS: &[&str] = &[
+    "default",
+    "default_factory",
+    "repr",
+    "skip",
+    "validate",
+    "hash",
+    "compare",
+    "skip_serializing_if",
+    "repr_limit",
+    "into",
+    "flatten",
+    "base",
+    "debug_with",
+    "lazy",
+    "order_desc",
+    "cow_getter",
+];
+
+/// Options from the struct-level `#[dataclass(...)]` attribute.
+///
+/// `init`, `repr`, `eq`, `order`, `hash`, `frozen` and `kw_only` mirror the
+/// flags on `dataclasses_lib::def::Dataclass`, with Python's defaults: only
+/// `init`, `repr` and `eq` start out on. `repr` departs from Python in what it
+/// does: Rust types usually derive `Debug` themselves, so it generates nothing
+/// and only permits the opt-in `debug` and `display`.
+pub struct DataclassAttrs {
+    pub iter_fields: bool,
+    /// Emits `schema` and `schema_hash`.
+    pub schema: bool,
+    /// `borrow` infers the target from the field type; `borrow = "str"` names it.
+    pub borrow: Option<Option<Type>>,
+    pub frozen: bool,
+    /// A `&mut self` method run on every newly built value of a frozen type.
+    pub freeze_after: Option<Ident>,
+    pub eq: bool,
+    /// Other structs with the same fields that this one compares equal to.
+    pub eq_with: Vec<Path>,
+    pub accessors: bool,
+    pub builder: bool,
+    /// Emits `Name::EMPTY`, a `const` with every field at its default.
+    pub const_default: bool,
+    /// A `fn(&self) -> Result<(), String>` method checking cross-field invariants.
+    pub validate: Option<Path>,
+    pub option_ctor: bool,
+    /// `hash` or `hash(include_type)`: generate `Hash` over the hashed fields.
+    pub hash: Option<HashOptions>,
+    /// Emits `NameRef<'a>`, a view holding a reference to each field.
+    pub ref_view: bool,
+    /// Emits a `push_<field>` method for each `Vec` field.
+    pub collection_helpers: bool,
+    /// Emits `as_dict` and `as_ordered_dict`, serializing each represented field.
+    pub as_dict: bool,
+    /// Emits `as_vec` and `try_from_values`, converting to and from positional values.
+    pub as_vec: bool,
+    /// Emits a `Debug` impl that leaves out `repr = false` fields.
+    pub debug: bool,
+    pub boxed_ctor: bool,
+    pub reset: bool,
+    /// Prepended to every accessor name, e.g. `get_`.
+    pub getter_prefix: Option<LitStr>,
+    /// Defaults every `Option` field without its own default to `None`.
+    pub default_all_optional: bool,
+    /// `eq_order = "a, b"`: fields the generated `PartialEq` compares first.
+    pub eq_order: Vec<Member>,
+    /// A `fn(&self) -> impl PartialEq` method whose result decides equality.
+    pub compare_by: Option<Ident>,
+    /// Emits `set_<field>` and `clear_<field>` for each `Option` field.
+    pub option_helpers: bool,
+    pub from_str: bool,
+    /// `hash_by(a, b)`: the only fields the generated `Hash` feeds.
+    pub hash_by: Vec<Member>,
+    /// Emits a `PartialOrd` comparing fields in the same order as `PartialEq`.
+    pub order: bool,
+    /// `order(total)`: also emits `Ord`, for types that derive `Eq`.
+    pub order_total: bool,
+    /// Emits `min_value` and `max_value` from each numeric field's `MIN` and `MAX`.
+    pub bounded: bool,
+    pub from_field: bool,
+    /// Emits `Borrow<Inner>` so maps keyed by a wrapper can be queried with its inner value.
+    pub equivalent: bool,
+    /// Emits `Default` even with required fields, filling them from their types' `Default`.
+    pub default_derive: bool,
+    /// `frozen` plus `accessors`, with every field private so the getters are the only access path.
+    pub freeze: bool,
+    /// Visits fields by name as `dyn Any`, mutably too unless frozen.
+    pub dynamic: bool,
+    /// Implements `Display` in the `Name(field=value, ..)` form.
+    pub display: bool,
+    /// Adds `with_<field>` methods returning a copy with one field replaced.
+    pub with: bool,
+    /// `order`, `hash`, `display`, `as_dict` and `with` together; see the derive's docs.
+    pub full: bool,
+    /// Exposed as `NAMESPACE` and qualifying the name in `schema`, for cross-language schema registries.
+    pub namespace: Option<LitStr>,
+    /// Keeps `new` and the constructors forwarding to it private, so other modules go through the builder.
+    pub private_new: bool,
+    /// A dataclass sharing this one's `base` fields, which gets a `From` projection.
+    pub extends: Option<Type>,
+    /// Converts into a tuple of the fields through `TryFrom`, running the validators first.
+    pub try_into_tuple: bool,
+    /// Compares float fields by bit pattern, so `NaN` equals itself and `Eq` can be implemented.
+    pub total_float_eq: bool,
+    /// Prepended to the builder's setter names, e.g. `set_`.
+    pub builder_setter_prefix: Option<LitStr>,
+    /// Builds the whole `Default` value, taking precedence over the field defaults.
+    pub default_fn: Option<Path>,
+    /// Makes `new` a `const fn`, spelling defaults the way `const_default` does.
+    pub const_new: bool,
+    /// Adds `modified`, returning an updated clone and leaving `self` alone.
+    pub modified: bool,
+    /// Python's `init`: `init = false` leaves out `new` and the constructors built on it.
+    pub init: bool,
+    /// Permits `debug` and `display`; unlike Python's `repr`, it generates nothing itself.
+    /// `repr = false` rules out `debug` and keeps `full` from adding `display`.
+    pub repr: bool,
+    /// Python's `kw_only`: fields are set by name through the builder, with no positional `new`.
+    pub kw_only: bool,
+    /// `dynamic` plus `with_field`, updating a `Clone` of the value one field at a time by name.
+    pub with_field: bool,
+    /// `rename_all = "..."` or `rename_all(keys = "...")`: field-name keys.
+    pub rename_keys: Option<RenameRule>,
+    /// `rename_all(getters = "...")`: accessor method names.
+    pub rename_getters: Option<RenameRule>,
+}
+
+impl Default for DataclassAttrs {
+    fn default() -> Self {
+        Self {
+            iter_fields: false,
+            schema: false,
+            borrow: None,
+            frozen: false,
+            freeze_after: None,
+            eq: true,
+            eq_with: Vec::new(),
+            accessors: false,
+            builder: false,
+            const_default: false,
+            validate: None,
+            option_ctor: false,
+            hash: None,
+            ref_view: false,
+            collection_helpers: false,
+            as_dict: false,
+            as_vec: false,
+            debug: false,
+            boxed_ctor: false,
+            reset: false,
+            getter_prefix: None,
+            default_all_optional: false,
+            eq_order: Vec::new(),
+            compare_by: None,
+            option_helpers: false,
+            from_str: false,
+            hash_by: Vec::new(),
+            order: false,
+            order_total: false,
+            bounded: false,
+            from_field: false,
+            equivalent: false,
+            default_derive: false,
+            freeze: false,
+            dynamic: false,
+            display: false,
+            with: false,
+            full: false,
+            namespace: None,
+            private_new: false,
+            extends: None,
+            try_into_tuple: false,
+            total_float_eq: false,
+            builder_setter_prefix: None,
+            default_fn: None,
+            const_new: false,
+            modified: false,
+            init: true,
+            repr: true,
+            kw_only: false,
+            with_field: false,
+            rename_keys: None,
+            rename_getters: None,
+        }
+    }
+}
+
+/// Options from a field-level `#[dataclass(...)]` attribute.
+pub struct FieldAttrs {
+    pub default: Option<FieldDefault>,
+    pub repr: bool,
+    pub skip: bool,
+    /// A `fn(&T) -> Result<(), String>` checking this field's value.
+    pub validate: Option<Path>,
+    /// Whether the field feeds the generated `Hash`; follows `compare` unless set.
+    pub hash: bool,
+    /// Whether the field takes part in the generated `PartialEq`.
+    pub compare: bool,
+    /// A `fn(&T) -> bool` that leaves the field out of `as_dict` when true.
+    pub skip_serializing_if: Option<Path>,
+    /// The most items of a collection field the `Debug` impl prints.
+    pub repr_limit: Option<usize>,
+    /// Whether `new` takes the field as `impl Into<T>`.
+    pub into: bool,
+    /// Whether `as_dict` merges this dataclass field's own entries into the parent's.
+    pub flatten: bool,
+    /// Shared with the `extends` dataclass and copied into it by the projection.
+    pub base: bool,
+    /// Formats the field in `Debug` output with `fn(&T, &mut Formatter) -> fmt::Result`.
+    pub debug_with: Option<Path>,
+    /// A factory filling a `OnceCell`/`OnceLock` field on first access through its getter.
+    pub lazy: Option<Path>,
+    /// Orders this field descending in the generated `PartialOrd`.
+    pub order_desc: bool,
+    /// Adds a `<field>_cow` accessor borrowing a `String` or `Vec<T>` as a `Cow`.
+    pub cow_getter: bool,
+}
+
+impl Default for FieldAttrs {
+    fn default() -> Self {
+        Self {
+            default: None,
+            repr: true,
+            skip: false,
+            validate: None,
+            hash: true,
+            compare: true,
+            skip_serializing_if: None,
+            repr_limit: None,
+            into: false,
+            flatten: false,
+            base: false,
+            debug_with: None,
+            lazy: None,
+            order_desc: false,
+            cow_getter: false,
+        }
+    }
+}
+
+/// Options from a variant-level `#[dataclass(...)]` attribute.
+#[derive(Default)]
+pub struct VariantAttrs {
+    /// Whether `Default` builds this variant.
+    pub default: bool,
+}
+
+/// Where a field's value comes from when `new` doesn't take it.
+pub enum FieldDefault {
+    /// `#[dataclass(default)]`: the field type's `Default` impl. The span
+    /// points type errors at the attribute.
+    Trait(Span),
+    /// `#[dataclass(default = "expr")]`: an expression evaluated per construction.
+    Expr(Expr),
+    /// `#[dataclass(default_factory = "path")]`: a function called per construction.
+    Factory(Path),
+}
+
+impl DataclassAttrs {
+    /// Parses an enum's attributes, rejecting the options that only make
+    /// sense for structs.
+    pub fn parse_enum(attrs: &[Attribute]) -> Result<Self> {
+        for meta in dataclass_metas(attrs)? {
+            let name = meta_name(&meta);
+            if ENUM_OPTIONS.contains(&name.as_str()) {
+                continue;
+            }
+            if STRUCT_OPTIONS.contains(&name.as_str()) {
+                return Err(Error::new_spanned(
+                    meta.path(),
+                    format!("`{}` is only supported on structs", name),
+                ));
+            }
+            return Err(unknown_option(&meta, ENUM_OPTIONS));
+        }
+        Self::parse(attrs)
+    }
+
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = Self::default();
+        // Options generating `&mut self` methods, which `frozen` rules out.
+        let mut mutators = Vec::new();
+        // Checked against `builder` once every option has been read.
+        let mut private_new = None;
+        // Checked against `debug` and `display` once every option has been read.
+        let mut repr = None;
+        // `init = false` or `kw_only`, checked against the options calling `new`.
+        let mut no_init = None;
+        // Checked against `order`, whose `PartialOrd` needs the `PartialEq`.
+        let mut no_eq = None;
+        for meta in dataclass_metas(attrs)? {
+            let name = meta_name(&meta);
+            match name.as_str() {
+                "iter_fields" => parsed.iter_fields = parse_bool(&meta)?,
+                "schema" => parsed.schema = parse_bool(&meta)?,
+                "borrow" => {
+                    parsed.borrow = Some(match &meta {
+                        Meta::Path(_) => None,
+                        _ => Some(parse_lit_str(&meta)?.parse()?),
+                    })
+                }
+                "frozen" => parsed.frozen = parse_bool(&meta)?,
+                "freeze_after" => parsed.freeze_after = Some(parse_lit_str(&meta)?.parse()?),
+                "eq" => {
+                    parsed.eq = parse_bool(&meta)?;
+                    no_eq = Some(meta.clone());
+                }
+                "eq_with" => parsed.eq_with.push(parse_lit_str(&meta)?.parse()?),
+                "accessors" => parsed.accessors = parse_bool(&meta)?,
+                "builder" => parsed.builder = parse_bool(&meta)?,
+                "const_default" => parsed.const_default = parse_bool(&meta)?,
+                "validate" => parsed.validate = Some(parse_lit_str(&meta)?.parse()?),
+                "rename_all" => parsed.parse_rename_all(&meta)?,
+                "option_ctor" => parsed.option_ctor = parse_bool(&meta)?,
+                "hash" => parsed.hash = HashOptions::parse(&meta)?,
+                "ref_view" => parsed.ref_view = parse_bool(&meta)?,
+                "collection_helpers" => {
+                    parsed.collection_helpers = parse_bool(&meta)?;
+                    if parsed.collection_helpers {
+                        mutators.push(meta.clone());
+                    }
+                }
+                "as_dict" => parsed.as_dict = parse_bool(&meta)?,
+                "as_vec" => parsed.as_vec = parse_bool(&meta)?,
+                "debug" => parsed.debug = parse_bool(&meta)?,
+                "boxed_ctor" => parsed.boxed_ctor = parse_bool(&meta)?,
+                "reset" => {
+                    parsed.reset = parse_bool(&meta)?;
+                    if parsed.reset {
+                        mutators.push(meta.clone());
+                    }
+                }
+                "getter_prefix" => parsed.getter_prefix = Some(parse_method_prefix(&meta)?),
+                "default_all_optional" => parsed.default_all_optional = parse_bool(&meta)?,
+                "eq_order" => {
+                    parsed.eq_order = parse_lit_str(&meta)?
+                        .parse_with(Punctuated::<Member, Token![,]>::parse_terminated)?
+                        .into_iter()
+                        .collect()
+                }
+                "compare_by" => parsed.compare_by = Some(parse_lit_str(&meta)?.parse()?),
+                "option_helpers" => {
+                    parsed.option_helpers = parse_bool(&meta)?;
+                    if parsed.option_helpers {
+                        mutators.push(meta.clone());
+                    }
+                }
+                "from_str" => parsed.from_str = parse_bool(&meta)?,
+                "hash_by" => parsed.hash_by = parse_member_list(&meta)?,
+                "order" => parsed.parse_order(&meta)?,
+                "bounded" => parsed.bounded = parse_bool(&meta)?,
+                "from_field" => parsed.from_field = parse_bool(&meta)?,
+                "equivalent" => parsed.equivalent = parse_bool(&meta)?,
+                "default_derive" => parsed.default_derive = parse_bool(&meta)?,
+                "freeze" => parsed.freeze = parse_bool(&meta)?,
+                "dynamic" => parsed.dynamic = parse_bool(&meta)?,
+                "display" => parsed.display = parse_bool(&meta)?,
+                "with" => parsed.with = parse_bool(&meta)?,
+                "full" => parsed.full = parse_bool(&meta)?,
+                "namespace" => parsed.namespace = Some(parse_lit_str(&meta)?),
+                "private_new" => {
+                    parsed.private_new = parse_bool(&meta)?;
+                    if parsed.private_new {
+                        private_new = Some(meta.clone());
+                    }
+                }
+                "extends" => parsed.extends = Some(parse_lit_str(&meta)?.parse()?),
+                "try_into_tuple" => parsed.try_into_tuple = parse_bool(&meta)?,
+                "total_float_eq" => parsed.total_float_eq = parse_bool(&meta)?,
+                "builder_setter_prefix" => {
+                    parsed.builder_setter_prefix = Some(parse_method_prefix(&meta)?)
+                }
+                "default_fn" => parsed.default_fn = Some(parse_lit_str(&meta)?.parse()?),
+                "const_new" => parsed.const_new = parse_bool(&meta)?,
+                "modified" => parsed.modified = parse_bool(&meta)?,
+                "init" => {
+                    parsed.init = parse_bool(&meta)?;
+                    if !parsed.init {
+                        no_init = Some(meta.clone());
+                    }
+                }
+                "repr" => {
+                    parsed.repr = parse_bool(&meta)?;
+                    repr = Some(meta.clone());
+                }
+                "kw_only" => {
+                    parsed.kw_only = parse_bool(&meta)?;
+                    if parsed.kw_only {
+                        no_init = Some(meta.clone());
+                    }
+                }
+                "with_field" => parsed.with_field = parse_bool(&meta)?,
+                _ => return Err(unknown_option(&meta, STRUCT_OPTIONS)),
+            }
+        }
+        match repr {
+            Some(meta) if !parsed.repr && (parsed.debug || parsed.display) => {
+                let option = if parsed.debug { "debug" } else { "display" };
+                return Err(Error::new_spanned(
+                    meta.path(),
+                    format!("`repr = false` leaves out `{}`", option),
+                ));
+            }
+            _ => {}
+        }
+        if parsed.kw_only {
+            parsed.init = false;
+            parsed.builder = true;
+        }
+        let built_on_new = [
+            ("option_ctor", parsed.option_ctor),
+            ("boxed_ctor", parsed.boxed_ctor),
+            ("from_field", parsed.from_field),
+            ("const_new", parsed.const_new),
+            ("private_new", parsed.private_new),
+        ];
+        match (&no_init, built_on_new.iter().find(|(_, on)| *on)) {
+            (Some(meta), Some((option, _))) if !parsed.init => {
+                return Err(Error::new_spanned(
+                    meta.path(),
+                    format!(
+                        "`{}` builds on `new`, which `{}` leaves out",
+                        option,
+                        meta_name(meta)
+                    ),
+                ))
+            }
+            _ => {}
+        }
+        if parsed.full {
+            parsed.order = true;
+            parsed.display = parsed.repr;
+            parsed.as_dict = true;
+            parsed.with = true;
+            parsed.hash.get_or_insert_with(HashOptions::default);
+        }
+        if parsed.with_field {
+            parsed.dynamic = true;
+        }
+        match no_eq {
+            Some(meta) if !parsed.eq && parsed.order => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "`order` builds on the generated `PartialEq`, which `eq = false` leaves out",
+                ))
+            }
+            _ => {}
+        }
+        if parsed.freeze {
+            parsed.frozen = true;
+            parsed.accessors = true;
+        }
+        if let Some(method) = &parsed.freeze_after {
+            if !parsed.frozen {
+                return Err(Error::new_spanned(
+                    method,
+                    "`freeze_after` only applies to `frozen` dataclasses",
+                ));
+            }
+            if parsed.const_default || parsed.const_new {
+                let option = if parsed.const_default {
+                    "const_default"
+                } else {
+                    "const_new"
+                };
+                return Err(Error::new_spanned(
+                    method,
+                    format!("`{}` cannot run a `freeze_after` hook", option),
+                ));
+            }
+        }
+        // Public ways to build a value that skip the validators.
+        let unvalidated = [
+            ("default_derive", parsed.default_derive),
+            ("default_fn", parsed.default_fn.is_some()),
+            ("from_field", parsed.from_field),
+            ("from_str", parsed.from_str),
+            ("const_default", parsed.const_default),
+        ];
+        match private_new {
+            Some(meta) if parsed.private_new && !parsed.builder => return Err(Error::new_spanned(
+                meta.path(),
+                "`private_new` needs `builder`, or nothing outside the module could construct it",
+            )),
+            Some(meta) if parsed.private_new => {
+                if let Some((option, _)) = unvalidated.iter().find(|(_, on)| *on) {
+                    return Err(Error::new_spanned(
+                        meta.path(),
+                        format!(
+                            "`{}` builds values without the validators `private_new` routes construction through",
+                            option
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        match &parsed.getter_prefix {
+            Some(prefix) if !parsed.accessors => {
+                return Err(Error::new_spanned(
+                    prefix,
+                    "`getter_prefix` only applies with `accessors`",
+                ))
+            }
+            _ => {}
+        }
+        match &parsed.builder_setter_prefix {
+            Some(prefix) if !parsed.builder => {
+                return Err(Error::new_spanned(
+                    prefix,
+                    "`builder_setter_prefix` only applies with `builder`",
+                ))
+            }
+            _ => {}
+        }
+        if !parsed.hash_by.is_empty() && parsed.hash.is_none() {
+            parsed.hash = Some(HashOptions::default());
+        }
+        match mutators.first() {
+            Some(meta) if parsed.frozen => {
+                return Err(Error::new_spanned(
+                    meta.path(),
+                    format!(
+                        "`{}` mutates fields, so it can't be used on `frozen` dataclasses",
+                        meta_name(meta)
+                    ),
+                ))
+            }
+            _ => {}
+        }
+        Ok(parsed)
+    }
+}
+
+impl DataclassAttrs {
+    /// `order`, `order = false` or `order(total)`.
+    fn parse_order(&mut self, meta: &Meta) -> Result<()> {
+        let list = match meta {
+            Meta::List(list) => list,
+            _ => {
+                self.order = parse_bool(meta)?;
+                return Ok(());
+            }
+        };
+        self.order = true;
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(meta) if meta.path().is_ident("total") => {
+                    self.order_total = parse_bool(meta)?
+                }
+                NestedMeta::Meta(meta) => return Err(unknown_option(meta, &["total"])),
+                NestedMeta::Lit(lit) => {
+                    return Err(Error::new_spanned(lit, "expected an order option"))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_rename_all(&mut self, meta: &Meta) -> Result<()> {
+        let list = match meta {
+            Meta::List(list) => list,
+            _ => {
+                self.rename_keys = Some(RenameRule::parse(&parse_lit_str(meta)?)?);
+                return Ok(());
+            }
+        };
+        for nested in &list.nested {
+            let meta = match nested {
+                NestedMeta::Meta(meta) => meta,
+                NestedMeta::Lit(lit) => {
+                    return Err(Error::new_spanned(lit, "expected `keys` or `getters`"))
+                }
+            };
+            let lit = parse_lit_str(meta)?;
+            let rule = RenameRule::parse(&lit)?;
+            match meta_name(meta).as_str() {
+                "keys" => self.rename_keys = Some(rule),
+                "getters" if rule.keeps_identifiers() => self.rename_getters = Some(rule),
+                "getters" => {
+                    return Err(Error::new_spanned(
+                        lit,
+                        "getter names must stay valid identifiers",
+                    ))
+                }
+                _ => return Err(unknown_option(meta, &["keys", "getters"])),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options from `hash(...)`.
+#[derive(Default)]
+pub struct HashOptions {
+    /// Hashes the type name first, so equal field values in different
+    /// dataclasses don't collide.
+    pub include_type: bool,
+    /// Hashes each field's name before its value, so swapping the values of
+    /// two same-typed fields changes the hash.
+    pub name_seeded: bool,
+}
+
+impl HashOptions {
+    /// `None` for `hash = false`.
+    fn parse(meta: &Meta) -> Result<Option<Self>> {
+        let mut parsed = Self::default();
+        if let Meta::List(list) = meta {
+            for nested in &list.nested {
+                match nested {
+                    NestedMeta::Meta(meta) if meta.path().is_ident("include_type") => {
+                        parsed.include_type = parse_bool(meta)?
+                    }
+                    NestedMeta::Meta(meta) if meta.path().is_ident("name_seeded") => {
+                        parsed.name_seeded = parse_bool(meta)?
+                    }
+                    NestedMeta::Meta(meta) => {
+                        return Err(unknown_option(meta, &["include_type", "name_seeded"]))
+                    }
+                    NestedMeta::Lit(lit) => {
+                        return Err(Error::new_spanned(lit, "expected a hash option"))
+                    }
+                }
+            }
+        } else if !parse_bool(meta)? {
+            return Ok(None);
+        }
+        Ok(Some(parsed))
+    }
+}
+
+/// A serde-style case convention for `rename_all`, applied to snake_case
+/// field names.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    const NAMES: &'static [&'static str] = &[
+        "lowercase",
+        "UPPERCASE",
+        "PascalCase",
+        "camelCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "SCREAMING-KEBAB-CASE",
+    ];
+
+    fn parse(lit: &LitStr) -> Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                return Err(Error::new_spanned(
+                    lit,
+                    format!(
+                        "unknown case convention; expected one of: {}",
+                        Self::NAMES.join(", ")
+                    ),
+                ))
+            }
+        })
+    }
+
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Pascal => name.to_pascal_case(),
+            Self::Camel => name.to_camel_case(),
+            Self::Snake => name.to_string(),
+            Self::ScreamingSnake => name.to_screaming_snake_case(),
+            Self::Kebab => name.to_kebab_case(),
+            Self::ScreamingKebab => name.to_kebab_case().to_uppercase(),
+        }
+    }
+
+    fn keeps_identifiers(self) -> bool {
+        !matches!(self, Self::Kebab | Self::ScreamingKebab)
+    }
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = Self::default();
+        for meta in dataclass_metas(attrs)? {
+            let name = meta_name(&meta);
+            match name.as_str() {
+                "default" => parsed.default = parse_bool(&meta)?,
+                _ => return Err(unknown_option(&meta, VARIANT_OPTIONS)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = Self::default();
+        // Like Python's `field(hash=None)`, an unset `hash` follows `compare`.
+        let mut hash = None;
+        for meta in dataclass_metas(attrs)? {
+            let name = meta_name(&meta);
+            match name.as_str() {
+                "default" | "default_factory" if parsed.default.is_some() => {
+                    return Err(Error::new_spanned(meta, "field already has a default"))
+                }
+                "default" => {
+                    parsed.default = Some(match &meta {
+                        Meta::Path(path) => FieldDefault::Trait(path.span()),
+                        _ => FieldDefault::Expr(parse_default_expr(&parse_lit_str(&meta)?)?),
+                    })
+                }
+                "default_factory" => {
+                    parsed.default = Some(FieldDefault::Factory(parse_lit_str(&meta)?.parse()?))
+                }
+                "repr" => parsed.repr = parse_bool(&meta)?,
+                "skip" => parsed.skip = parse_bool(&meta)?,
+                "validate" => parsed.validate = Some(parse_lit_str(&meta)?.parse()?),
+                "hash" => hash = Some((parse_bool(&meta)?, meta.clone())),
+                "compare" => parsed.compare = parse_bool(&meta)?,
+                "skip_serializing_if" => {
+                    parsed.skip_serializing_if = Some(parse_lit_str(&meta)?.parse()?)
+                }
+                "repr_limit" => parsed.repr_limit = Some(parse_usize(&meta)?),
+                "into" => parsed.into = parse_bool(&meta)?,
+                "flatten" => parsed.flatten = parse_bool(&meta)?,
+                "base" => parsed.base = parse_bool(&meta)?,
+                "debug_with" => parsed.debug_with = Some(parse_lit_str(&meta)?.parse()?),
+                "lazy" => parsed.lazy = Some(parse_lit_str(&meta)?.parse()?),
+                "order_desc" => parsed.order_desc = parse_bool(&meta)?,
+                "cow_getter" => parsed.cow_getter = parse_bool(&meta)?,
+                _ => return Err(unknown_option(&meta, FIELD_OPTIONS)),
+            }
+        }
+        parsed.hash = match hash {
+            Some((true, meta)) if !parsed.compare => return Err(Error::new_spanned(
+                meta,
+                "a `compare = false` field can't be hashed: equal values would hash differently",
+            )),
+            Some((hash, _)) => hash,
+            None => parsed.compare,
+        };
+        if let Some(factory) = &parsed.lazy {
+            // The cell starts empty, so `new` never takes it.
+            if parsed.default.is_some() {
+                return Err(Error::new_spanned(
+                    factory,
+                    "a `lazy` field starts empty; it can't also have a default",
+                ));
+            }
+            parsed.default = Some(FieldDefault::Trait(factory.span()));
+        }
+        match &parsed.debug_with {
+            Some(path) if parsed.repr_limit.is_some() => {
+                return Err(Error::new_spanned(
+                    path,
+                    "`debug_with` already decides how the field prints; drop `repr_limit`",
+                ))
+            }
+            _ => {}
+        }
+        Ok(parsed)
+    }
+}
+
+/// Flattens every `#[dataclass(...)]` attribute into its nested items,
+/// ignoring attributes that belong to other macros.
+fn dataclass_metas(attrs: &[Attribute]) -> Result<Vec<Meta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("dataclass")) {
+        match attr.parse_meta()? {
+            Meta::Path(_) => {}
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(meta) => metas.push(meta),
+                        NestedMeta::Lit(lit) => {
+                            return Err(Error::new_spanned(lit, "expected a dataclass option"))
+                        }
+                    }
+                }
+            }
+            meta @ Meta::NameValue(_) => {
+                return Err(Error::new_spanned(meta, "expected `#[dataclass(...)]`"))
+            }
+        }
+    }
+    Ok(metas)
+}
+
+fn meta_name(meta: &Meta) -> String {
+    meta.path()
+        .get_ident()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// Points at the unrecognized option name, suggesting the closest known one.
+fn unknown_option(meta: &Meta, options: &[&str]) -> Error {
+    let path = meta.path();
+    let name = meta_name(meta);
+    let mut message = format!("unknown dataclass attribute `{}`", name);
+    let closest = options
+        .iter()
+        .map(|option| (edit_distance(&name, option), option))
+        .min();
+    if let Some((distance, option)) = closest {
+        if distance <= 2 {
+            message.push_str(&format!("; did you mean `{}`?", option));
+        }
+    }
+    message.push_str(&format!("\nexpected one of: {}", options.join(", ")));
+    Error::new_spanned(path, message)
+}
+
+/// Levenshtein distance, for typo suggestions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Accepts both the bare flag form (`skip`) and the explicit `skip = false`.
+fn parse_bool(meta: &Meta) -> Result<bool> {
+    match meta {
+        Meta::Path(_) => Ok(true),
+        Meta::NameValue(nv) => match &nv.lit {
+            Lit::Bool(value) => Ok(value.value),
+            lit => Err(Error::new_spanned(lit, "expected `true` or `false`")),
+        },
+        Meta::List(list) => Err(Error::new_spanned(list, "expected a boolean flag")),
+    }
+}
+
+/// Reads the string in `name = "..."`.
+fn parse_lit_str(meta: &Meta) -> Result<LitStr> {
+    match meta {
+        Meta::NameValue(nv) => match &nv.lit {
+            Lit::Str(value) => Ok(value.clone()),
+            lit => Err(Error::new_spanned(lit, "expected a string literal")),
+        },
+        _ => Err(Error::new_spanned(meta, "expected `name = \"...\"`")),
+    }
+}
+
+/// Reads the integer in `name = N`.
+fn parse_usize(meta: &Meta) -> Result<usize> {
+    match meta {
+        Meta::NameValue(nv) => match &nv.lit {
+            Lit::Int(value) => value.base10_parse(),
+            lit => Err(Error::new_spanned(lit, "expected an integer literal")),
+        },
+        _ => Err(Error::new_spanned(meta, "expected `name = N`")),
+    }
+}
+
+/// Reads the fields named in `name(a, b)`, or `name(0, 1)` for tuple structs.
+fn parse_member_list(meta: &Meta) -> Result<Vec<Member>> {
+    let list = match meta {
+        Meta::List(list) => list,
+        _ => return Err(Error::new_spanned(meta, "expected `name(field, ...)`")),
+    };
+    list.nested
+        .iter()
+        .map(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => path
+                .get_ident()
+                .map(|ident| Member::Named(ident.clone()))
+                .ok_or_else(|| Error::new_spanned(path, "expected a field name")),
+            NestedMeta::Lit(Lit::Int(index)) => Ok(Member::Unnamed(Index {
+                index: index.base10_parse()?,
+                span: index.span(),
+            })),
+            _ => Err(Error::new_spanned(nested, "expected a field name")),
+        })
+        .collect()
+}
+
+/// Reads a method name prefix, which must start a valid identifier.
+fn parse_method_prefix(meta: &Meta) -> Result<LitStr> {
+    let lit = parse_lit_str(meta)?;
+    if syn::parse_str::<Ident>(&format!("{}field", lit.value())).is_err() {
+        return Err(Error::new_spanned(
+            lit,
+            format!(
+                "`{}` must form valid identifiers with field names",
+                meta_name(meta)
+            ),
+        ));
+    }
+    Ok(lit)
+}
+
+/// Parses a `default = "..."` expression, rejecting `?` and `return`: the
+/// expression is spliced into `new`, so either would exit the constructor.
+/// An inline `const { .. }` block parses as an opaque expression and is
+/// re-emitted verbatim, so it is still evaluated at compile time.
+fn parse_default_expr(lit: &LitStr) -> Result<Expr> {
+    let expr: Expr = lit.parse()?;
+    let mut finder = EarlyExitFinder::default();
+    finder.visit_expr(&expr);
+    match finder.found {
+        Some(exit) => Err(Error::new_spanned(
+            exit,
+            "dataclass defaults must be simple expressions; `?` and `return` \
+             would exit the generated constructor",
+        )),
+        None => Ok(expr),
+    }
+}
+
+/// Finds the first `?` or `return` that belongs to the expression itself
+/// rather than to a closure, async block or item nested inside it.
+#[derive(Default)]
+struct EarlyExitFinder<'ast> {
+    found: Option<&'ast Expr>,
+}
+
+impl<'ast> Visit<'ast> for EarlyExitFinder<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::Try(_) | Expr::Return(_) if self.found.is_none() => self.found = Some(expr),
+            Expr::Closure(_) | Expr::Async(_) => {}
+            _ => visit::visit_expr(self, expr),
+        }
+    }
+
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
@@ -1,20 +1,2908 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use proc_macro2::{Delimiter, Group, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DataStruct, DeriveInput, Error,
+    Fields, GenericArgument, Generics, Ident, Index, Lifetime, Member, Path, PathArguments, Type,
+    Visibility,
+};
 
+mod attr;
 
+use attr::{DataclassAttrs, FieldAttrs, FieldDefault, HashOptions, VariantAttrs};
+
+/// Derives Python-style dataclass behavior for a struct: a positional `new`
+/// taking every field without a default, `PartialEq`, `Default` when every
+/// field has a default, and `FIELD_COUNT`, `FIELD_NAMES` and `FIELD_TYPES`.
+/// Enums get `PartialEq` and, with one `#[dataclass(default)]` variant,
+/// `Default`.
+///
+/// # Struct options
+///
+/// Set in `#[dataclass(...)]` on the struct.
+///
+/// - `init`, `repr`, `eq`, `order`, `hash`, `frozen`, `kw_only`: Python's
+///   flags with Python's defaults, so only `init`, `repr` and `eq` start on.
+///   `init = false` leaves out `new` and the constructors built on it.
+///   `kw_only` does too, building through the builder instead. `repr` only
+///   permits `debug` and `display`; it generates nothing itself.
+/// - `order`: `PartialOrd` over the compared fields. `order(total)` adds
+///   `Ord` for types that also derive `Eq`. `order` needs `eq`.
+/// - `hash`, `hash(include_type)`, `hash(name_seeded)`, `hash_by(a, b)`:
+///   `Hash` over the compared fields, or exactly the named ones. `-0.0` hashes
+///   like `0.0` unless `total_float_eq` is set.
+/// - `full`: `order`, `hash`, `display` (unless `repr = false`), `as_dict` and
+///   `with` together. That is, the `PartialOrd`, `Hash` and `Display` impls
+///   plus the `as_dict`, `as_ordered_dict`, `as_kwargs`, `diff_dict` and
+///   `with_<field>` methods.
+/// - `eq_order = "a, b"`, `compare_by = "method"`, `eq_with = "Other"`,
+///   `total_float_eq`: which fields `PartialEq` compares first, a projection
+///   deciding equality instead, equality with a same-shaped struct, and
+///   bitwise float equality with `Eq`.
+/// - `builder`, `builder_setter_prefix = "set_"`, `private_new`: a builder
+///   running the validators, optionally as the only public constructor.
+/// - `validate = "method"`: a struct-level check run by `try_new`, the
+///   builder and every method building a new value.
+/// - `option_ctor`, `boxed_ctor`, `const_new`, `from_field`, `from_str`,
+///   `try_into_tuple`: `checked_new`, `new_boxed`, a `const fn new`, `From`
+///   the one required field, `FromStr` for a one-field wrapper, and `TryFrom`
+///   into a tuple of the fields.
+/// - `default_derive`, `default_fn = "path"`, `default_all_optional`,
+///   `const_default`: `Default` even with required fields, `Default` from a
+///   function, `None` defaults for `Option` fields, and a `const EMPTY`.
+/// - `frozen`, `freeze`, `freeze_after = "method"`: no `&mut self` methods;
+///   `frozen` plus private fields behind `accessors`; a hook run on every
+///   newly built value.
+/// - `accessors`, `getter_prefix = "get_"`, `rename_all = "..."`: getters,
+///   and how keys and getters are named.
+/// - `with`, `modified`, `reset`, `collection_helpers`, `option_helpers`:
+///   `with_<field>` copies, an updated clone, resetting to defaults,
+///   `push_<field>`, and `set_`/`clear_`/`take_<field>`.
+/// - `iter_fields`, `as_dict`, `as_vec`, `dynamic`, `with_field`: fields as
+///   serialized values, and by name through `dyn Any` or a `Value`.
+/// - `debug`, `display`: `Debug` leaving out `repr = false` fields, and
+///   `Display` as `Name(field=value, ..)`.
+/// - `schema`, `namespace = "..."`: a JSON description with `schema_hash`,
+///   and the namespace qualifying it.
+/// - `bounded`, `ref_view`, `borrow`, `equivalent`, `extends = "Base"`:
+///   `min_value`/`max_value`, a struct of references, `Borrow` for a wrapper,
+///   lookups by the inner value, and a `From` projection into a base.
+///
+/// # Field options
+///
+/// Set in `#[dataclass(...)]` on a field.
+///
+/// - `default`, `default = "expr"`, `default_factory = "path"`: what `new`
+///   fills the field with instead of taking it.
+/// - `repr = false`, `skip`, `skip_serializing_if = "path"`, `repr_limit = N`,
+///   `debug_with = "path"`: how the field shows up in `Debug` and the
+///   serialized forms.
+/// - `compare = false`, `hash = false`, `order_desc`: how it is compared,
+///   hashed and ordered. An uncompared field is never hashed.
+/// - `validate = "path"`, `into`, `flatten`, `base`, `lazy = "factory"`,
+///   `cow_getter`: a field check, an `impl Into<T>` parameter, merged
+///   `as_dict` entries, a field shared with `extends`, a `OnceCell` filled on
+///   first access, and a `Cow` getter.
+///
+/// # Defaults
+///
+/// A default expression runs afresh for every value, so unlike a Python
+/// mutable default argument, a `vec![..]` default is never shared between
+/// values. It is expanded in an impl carrying the struct's own where-clause,
+/// so it can rely on the bounds written there. Those are the only bounds it
+/// can rely on: apart from `Ty: Default` for a bare `default`, nothing such
+/// as `T: Clone` is added for it.
 #[proc_macro_derive(Dataclass, attributes(serde, dataclass, validate))]
 pub fn dataclass(input: TokenStream) -> TokenStream {
     // Parse the input tokens into a syntax tree
     let input = parse_macro_input!(input as DeriveInput);
 
-    // Build the output, possibly using quasi-quotation
-    let expanded = quote! {
-        // ...
-    };
+    // Build the output, reporting attribute errors at their source
+    let expanded = impl_dataclass(&input).unwrap_or_else(Error::into_compile_error);
 
     // Hand the output tokens back to the compiler
     TokenStream::from(expanded)
-}
\ No newline at end of file
+}
+
+/// Derives `Dataclass` for every struct or enum in the block, as if each were
+/// annotated with `#[derive(Dataclass)]`.
+#[proc_macro]
+pub fn derive_all(input: TokenStream) -> TokenStream {
+    let DeriveInputs(inputs) = parse_macro_input!(input as DeriveInputs);
+
+    let expanded = inputs.iter().map(|input| {
+        let generated = impl_dataclass(input).unwrap_or_else(Error::into_compile_error);
+        let item = without_dataclass_attrs(input);
+        quote! {
+            #item
+            #generated
+        }
+    });
+
+    TokenStream::from(quote!(#(#expanded)*))
+}
+
+/// The items in a `derive_all!` block.
+struct DeriveInputs(Vec<DeriveInput>);
+
+impl Parse for DeriveInputs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut inputs = Vec::new();
+        while !input.is_empty() {
+            inputs.push(input.parse()?);
+        }
+        Ok(DeriveInputs(inputs))
+    }
+}
+
+/// The item as written, minus the `#[dataclass(...)]` attributes that only a
+/// derive can accept.
+fn without_dataclass_attrs(input: &DeriveInput) -> DeriveInput {
+    fn strip(attrs: &mut Vec<Attribute>) {
+        attrs.retain(|attr| !attr.path.is_ident("dataclass"));
+    }
+
+    let mut item = input.clone();
+    strip(&mut item.attrs);
+    match &mut item.data {
+        Data::Struct(data) => data
+            .fields
+            .iter_mut()
+            .for_each(|field| strip(&mut field.attrs)),
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                strip(&mut variant.attrs);
+                variant
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| strip(&mut field.attrs));
+            }
+        }
+        Data::Union(data) => data
+            .fields
+            .named
+            .iter_mut()
+            .for_each(|field| strip(&mut field.attrs)),
+    }
+    item
+}
+
+/// A struct field together with its parsed `#[dataclass(...)]` options.
+struct DataclassField<'a> {
+    /// How the field is accessed: `self.name` or `self.0`.
+    member: Member,
+    /// The binding used for the field in generated parameter lists.
+    ident: Ident,
+    /// The field's name in `iter_fields` and `schema`, after `rename_all`.
+    key: String,
+    vis: &'a Visibility,
+    ty: &'a Type,
+    attrs: FieldAttrs,
+}
+
+impl DataclassField<'_> {
+    /// The expression `new` uses for a field it doesn't take as a parameter.
+    /// A bare `default` on an `Option` is spelled `None` so it never leans on
+    /// a `Default` impl. Every caller expands it inside an impl carrying the
+    /// struct's own where-clause, which is what the derive's docs promise.
+    /// The tokens keep the span of the attribute they came from, so type
+    /// errors are reported there.
+    fn default_expr(&self) -> Option<TokenStream2> {
+        self.attrs.default.as_ref().map(|default| match default {
+            FieldDefault::Trait(_) if option_inner(self.ty).is_some() => {
+                quote!(::core::option::Option::None)
+            }
+            FieldDefault::Trait(span) => {
+                let ty = self.ty;
+                quote_spanned!(*span=> <#ty as ::core::default::Default>::default())
+            }
+            FieldDefault::Expr(expr) => quote_spanned!(expr.span()=> #expr),
+            FieldDefault::Factory(path) => quote_spanned!(path.span()=> #path()),
+        })
+    }
+}
+
+/// Generated code names everything by absolute path (`::core`, `::serde`,
+/// `::serde_value`) so it expands the same regardless of what the deriving
+/// module imports or shadows.
+fn impl_dataclass(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if let Data::Enum(data) = &input.data {
+        return impl_enum(input, data);
+    }
+    let attrs = DataclassAttrs::parse(&input.attrs)?;
+    let fields = struct_fields(input, &attrs)?;
+    if attrs.freeze {
+        check_private_fields(&fields)?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let new = if attrs.init {
+        new_fn(name, &attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let field_count = fields.len();
+    let field_names = fields.iter().map(|field| &field.key);
+    let field_types = fields.iter().map(|field| field.ty);
+    let iter_fields = if attrs.iter_fields {
+        iter_fields_fn(&fields)
+    } else {
+        TokenStream2::new()
+    };
+    let dynamic = if attrs.dynamic {
+        dynamic_fns(&attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let as_dict = if attrs.as_dict {
+        as_dict_fns(&fields)
+    } else {
+        TokenStream2::new()
+    };
+    let as_vec = if attrs.as_vec {
+        as_vec_fns(&attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let accessors = if attrs.accessors {
+        accessor_fns(&attrs, &fields)?
+    } else if let Some(field) = fields.iter().find(|field| field.attrs.cow_getter) {
+        return Err(Error::new_spanned(
+            &field.member,
+            "`cow_getter` only applies with `accessors`",
+        ));
+    } else {
+        TokenStream2::new()
+    };
+    let lazy = lazy_fns(&attrs, &fields)?;
+    let collection_helpers = if attrs.collection_helpers {
+        collection_helper_fns(&fields)
+    } else {
+        TokenStream2::new()
+    };
+    let option_helpers = if attrs.option_helpers {
+        option_helper_fns(&fields)
+    } else {
+        TokenStream2::new()
+    };
+    let try_new = if attrs.init {
+        try_new_fn(&attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let checked_new = if attrs.option_ctor {
+        checked_new_fn(&attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let new_boxed = if attrs.boxed_ctor {
+        new_boxed_fn(&attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let reset = if attrs.reset {
+        reset_fn(&fields)
+    } else {
+        TokenStream2::new()
+    };
+    let with = if attrs.with {
+        with_fns(&attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let modified = if attrs.modified {
+        modified_fn(&attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let bounded = if attrs.bounded {
+        bounds_fns(&attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let const_default = if attrs.const_default {
+        const_default_item(&fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let schema = if attrs.schema {
+        schema_fn(name, &attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let namespace = attrs.namespace.as_ref().map(|namespace| {
+        quote! {
+            /// The namespace this dataclass is registered under.
+            pub const NAMESPACE: &'static str = #namespace;
+        }
+    });
+    // Under `private_new` an all-defaults `Default` would be a public way
+    // around the validating builder.
+    let derives_default = attrs.default_fn.is_some()
+        || attrs.default_derive
+        || (!attrs.private_new && fields.iter().all(|field| field.attrs.default.is_some()));
+    let default = if derives_default {
+        default_impl(input, &attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    // Only the generated `PartialEq` is known to exist.
+    let is_default = if derives_default && attrs.eq {
+        quote! {
+            /// Whether every field equals its default, e.g. to skip
+            /// serializing an untouched value.
+            pub fn is_default(&self) -> bool
+            where
+                Self: ::core::cmp::PartialEq + ::core::default::Default,
+            {
+                *self == <Self as ::core::default::Default>::default()
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+    let eq = if attrs.eq {
+        eq_impl(input, &attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let order = if attrs.order {
+        order_impl(input, &attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let eq_with = attrs
+        .eq_with
+        .iter()
+        .map(|other| eq_with_impl(input, &fields, other));
+    let debug = if attrs.debug {
+        debug_impl(input, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let display = if attrs.display {
+        display_impl(input, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let hash = match &attrs.hash {
+        Some(options) => hash_impl(input, &attrs, &fields, options)?,
+        None => TokenStream2::new(),
+    };
+    let builder = if attrs.builder {
+        builder_impl(input, &attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let from_str = if attrs.from_str {
+        from_str_impl(input, &attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let from_field = if attrs.from_field {
+        from_field_impl(input, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let try_into_tuple = if attrs.try_into_tuple {
+        try_into_tuple_impl(input, &attrs, &fields)
+    } else {
+        TokenStream2::new()
+    };
+    let extends = match &attrs.extends {
+        Some(base) => extends_impl(input, base, &fields)?,
+        None => TokenStream2::new(),
+    };
+    let ref_view = if attrs.ref_view {
+        ref_view_impl(input, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+    let borrow = match &attrs.borrow {
+        Some(target) => borrow_impl(input, &attrs, &fields, target.as_ref(), "borrow")?,
+        None => TokenStream2::new(),
+    };
+    let equivalent = if attrs.equivalent {
+        equivalent_impl(input, &attrs, &fields)?
+    } else {
+        TokenStream2::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The number of fields, usable as an array length.
+            pub const FIELD_COUNT: usize = #field_count;
+            /// Each field's name, in declaration order.
+            pub const FIELD_NAMES: &'static [&'static str] = &[#(#field_names),*];
+            /// Each field's type as written, in declaration order.
+            pub const FIELD_TYPES: &'static [&'static str] = &[#(::core::stringify!(#field_types)),*];
+            #namespace
+            #const_default
+            #new
+            #try_new
+            #checked_new
+            #new_boxed
+            #bounded
+            #accessors
+            #lazy
+            #collection_helpers
+            #option_helpers
+            #reset
+            #is_default
+            #with
+            #modified
+            #iter_fields
+            #dynamic
+            #as_dict
+            #as_vec
+            #schema
+        }
+
+        #default
+        #eq
+        #order
+        #(#eq_with)*
+        #debug
+        #display
+        #hash
+        #builder
+        #borrow
+        #equivalent
+        #from_str
+        #from_field
+        #try_into_tuple
+        #extends
+        #ref_view
+    })
+}
+
+/// Enums get the subset of dataclass behavior that applies per variant:
+/// `PartialEq`, and `Default` when one variant is marked
+/// `#[dataclass(default)]`.
+fn impl_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream2> {
+    let attrs = DataclassAttrs::parse_enum(&input.attrs)?;
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            Ok(DataclassVariant {
+                ident: &variant.ident,
+                attrs: VariantAttrs::parse(&variant.attrs)?,
+                fields: dataclass_fields(&variant.fields, &attrs)?,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let mut defaults = data
+        .variants
+        .iter()
+        .zip(&variants)
+        .filter(|(_, variant)| variant.attrs.default);
+    let default = match (defaults.next(), defaults.next()) {
+        (_, Some((extra, _))) => {
+            return Err(Error::new_spanned(
+                &extra.ident,
+                "only one variant can be `#[dataclass(default)]`",
+            ))
+        }
+        (Some((_, variant)), None) => enum_default_impl(input, variant),
+        (None, None) => TokenStream2::new(),
+    };
+    let eq = if attrs.eq {
+        enum_eq_impl(input, &variants)
+    } else {
+        TokenStream2::new()
+    };
+
+    Ok(quote! {
+        #default
+        #eq
+    })
+}
+
+/// An enum variant together with its parsed options and fields.
+struct DataclassVariant<'a> {
+    ident: &'a Ident,
+    attrs: VariantAttrs,
+    fields: Vec<DataclassField<'a>>,
+}
+
+/// `Default` builds the designated variant, filling each field from its
+/// dataclass default or, lacking one, its type's `Default` impl.
+fn enum_default_impl(input: &DeriveInput, variant: &DataclassVariant) -> TokenStream2 {
+    let name = &input.ident;
+    let ident = variant.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let mut bounds = Vec::new();
+    let inits: Vec<_> = variant
+        .fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let default = field.default_expr().unwrap_or_else(|| {
+                let ty = field.ty;
+                bounds.push(quote!(#ty: ::core::default::Default));
+                quote!(::core::default::Default::default())
+            });
+            quote!(#member: #default)
+        })
+        .collect();
+    bounds.extend(default_bounds(&variant.fields));
+    let where_clause = where_with(&input.generics, &bounds);
+
+    quote! {
+        impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                Self::#ident {
+                    #(#inits,)*
+                }
+            }
+        }
+    }
+}
+
+/// `PartialEq` holds when both sides are the same variant with equal fields.
+fn enum_eq_impl(input: &DeriveInput, variants: &[DataclassVariant]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let compared: Vec<_> = variants
+        .iter()
+        .flat_map(|variant| &variant.fields)
+        .filter(|field| field.attrs.compare)
+        .collect();
+    let bounds = generic_bounds(&input.generics, &compared, quote!(::core::cmp::PartialEq));
+    let where_clause = where_with(&input.generics, &bounds);
+    let arms = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let compared: Vec<_> = variant
+            .fields
+            .iter()
+            .filter(|field| field.attrs.compare)
+            .collect();
+        let members: Vec<_> = compared.iter().map(|field| &field.member).collect();
+        let lhs: Vec<_> = compared
+            .iter()
+            .map(|field| format_ident!("self_{}", field.ident))
+            .collect();
+        let rhs: Vec<_> = compared
+            .iter()
+            .map(|field| format_ident!("other_{}", field.ident))
+            .collect();
+        quote! {
+            (Self::#ident { #(#members: #lhs,)* .. }, Self::#ident { #(#members: #rhs,)* .. }) => {
+                true #(&& #lhs == #rhs)*
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #(#arms)*
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+fn struct_fields<'a>(
+    input: &'a DeriveInput,
+    attrs: &DataclassAttrs,
+) -> syn::Result<Vec<DataclassField<'a>>> {
+    match &input.data {
+        Data::Struct(data) => dataclass_fields(&data.fields, attrs),
+        _ => Err(Error::new_spanned(
+            &input.ident,
+            "Dataclass can only be derived for structs and enums",
+        )),
+    }
+}
+
+fn dataclass_fields<'a>(
+    fields: &'a Fields,
+    attrs: &DataclassAttrs,
+) -> syn::Result<Vec<DataclassField<'a>>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let (member, ident) = match &field.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.clone()),
+                None => (
+                    Member::Unnamed(Index::from(index)),
+                    format_ident!("field_{}", index),
+                ),
+            };
+            let name = match &member {
+                Member::Named(ident) => ident.to_string(),
+                Member::Unnamed(index) => index.index.to_string(),
+            };
+            let key = match attrs.rename_keys {
+                Some(rule) => rule.apply(&name),
+                None => name,
+            };
+            let mut field_attrs = FieldAttrs::parse(&field.attrs)?;
+            if attrs.default_all_optional
+                && field_attrs.default.is_none()
+                && option_inner(&field.ty).is_some()
+            {
+                field_attrs.default = Some(FieldDefault::Trait(field.ty.span()));
+            }
+            if let Some(FieldDefault::Trait(span)) = field_attrs.default {
+                check_defaultable(&field.ty, span)?;
+            }
+            Ok(DataclassField {
+                member,
+                ident,
+                key,
+                vis: &field.vis,
+                ty: &field.ty,
+                attrs: field_attrs,
+            })
+        })
+        .collect()
+}
+
+/// `NonZero*` integers have no `Default`, since there is no zero to fall back
+/// on, and neither does `Result`, which would have to pick a variant. Left
+/// alone, a bare `default` on either fails with a trait error that doesn't say
+/// what to write instead.
+fn check_defaultable(ty: &Type, span: Span) -> syn::Result<()> {
+    const NON_ZERO: &[&str] = &[
+        "NonZero",
+        "NonZeroU8",
+        "NonZeroU16",
+        "NonZeroU32",
+        "NonZeroU64",
+        "NonZeroU128",
+        "NonZeroUsize",
+        "NonZeroI8",
+        "NonZeroI16",
+        "NonZeroI32",
+        "NonZeroI64",
+        "NonZeroI128",
+        "NonZeroIsize",
+    ];
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    };
+    let example = match segment {
+        Some(segment) if NON_ZERO.iter().any(|name| segment.ident == name) => {
+            format!("{}::new(1).unwrap()", segment.ident)
+        }
+        Some(segment) if segment.ident == "Result" => "Ok(Default::default())".to_string(),
+        _ => return Ok(()),
+    };
+    Err(Error::new(
+        span,
+        format!(
+            "`{}` has no `Default`; give an explicit default such as \
+             `#[dataclass(default = \"{}\")]`",
+            ty.to_token_stream().to_string().replace(' ', ""),
+            example
+        ),
+    ))
+}
+
+/// `new` takes every field without a default, in declaration order, and fills
+/// the rest from their defaults. Interleaving defaulted fields never shifts
+/// the position of a required one. `new` is `pub` unless `private_new` is
+/// set: with any private field it is the only way other modules can build
+/// the struct.
+fn new_fn(
+    name: &Ident,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|field| field.attrs.default.is_none())
+        .collect();
+    let (params, _) = new_params(fields);
+    let allow = many_params_allow(&params);
+    let vis = constructor_vis(attrs);
+    let inits = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let ident = &field.ident;
+            if attrs.const_new {
+                if field.attrs.into {
+                    return Err(Error::new_spanned(
+                        member,
+                        "`into` fields can't be converted in a `const_new` constructor",
+                    ));
+                }
+                if field.attrs.default.is_some() {
+                    let value = const_default_value(field)?;
+                    return Ok(quote!(#member: #value));
+                }
+            }
+            Ok(match field.default_expr() {
+                Some(default) => quote!(#member: #default),
+                None if field.attrs.into => quote!(#member: ::core::convert::Into::into(#ident)),
+                None => quote!(#member: #ident),
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let body = construct(attrs, &quote!(Self), &inits);
+    // Under `const_new` no default goes through `Default`.
+    let (constness, bounds) = if attrs.const_new {
+        (quote!(const), Vec::new())
+    } else {
+        (TokenStream2::new(), default_bounds(fields))
+    };
+
+    let order = required
+        .iter()
+        .map(|field| format!("`{}`", field.ident))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut doc = if required.is_empty() {
+        format!("Creates a `{}` with every field defaulted.", name)
+    } else {
+        format!(
+            "Creates a `{}` from its required fields in declaration order: {}.",
+            name, order
+        )
+    };
+    if !attrs.private_new
+        && fields
+            .iter()
+            .any(|field| !matches!(field.vis, Visibility::Public(_)))
+    {
+        doc.push_str(" Some fields are private, so this is how other modules construct one.");
+    }
+
+    Ok(quote! {
+        #[doc = #doc]
+        #allow
+        #vis #constness fn new(#(#params),*) -> Self
+        where
+            #(#bounds,)*
+        {
+            #body
+        }
+    })
+}
+
+/// `Default` is derived when every field has a default, building the value
+/// the same way `new` would. Under `default_derive` it is derived regardless,
+/// filling fields `new` requires from their types' `Default`. A `default_fn`
+/// replaces all of that, for defaults where fields depend on each other.
+fn default_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    if let Some(default_fn) = &attrs.default_fn {
+        return quote! {
+            impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+                fn default() -> Self {
+                    #default_fn()
+                }
+            }
+        };
+    }
+    let mut bounds = default_bounds(fields);
+    let inits: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let default = field.default_expr().unwrap_or_else(|| {
+                let ty = field.ty;
+                bounds.push(quote_spanned!(ty.span()=> #ty: ::core::default::Default));
+                quote_spanned!(ty.span()=> <#ty as ::core::default::Default>::default())
+            });
+            quote!(#member: #default)
+        })
+        .collect();
+    let where_clause = where_with(&input.generics, &bounds);
+    let body = construct(attrs, &quote!(Self), &inits);
+
+    quote! {
+        impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                #body
+            }
+        }
+    }
+}
+
+/// A field's default as a const expression, for `const_default` and
+/// `const_new`.
+fn const_default_value(field: &DataclassField) -> syn::Result<TokenStream2> {
+    match &field.attrs.default {
+        Some(FieldDefault::Expr(expr)) => Ok(quote!(#expr)),
+        Some(FieldDefault::Factory(path)) => Ok(quote!(#path())),
+        Some(FieldDefault::Trait(_)) | None => const_zero(field.ty).ok_or_else(|| {
+            Error::new_spanned(
+                field.ty,
+                "no `const` default is known for this type; \
+                 add `#[dataclass(default = \"...\")]` with a const expression",
+            )
+        }),
+    }
+}
+
+/// Builds `Self` from field initializers, then hands a frozen value to its
+/// `freeze_after` hook as the last mutation it will ever see.
+fn construct(attrs: &DataclassAttrs, ty: &TokenStream2, inits: &[TokenStream2]) -> TokenStream2 {
+    match &attrs.freeze_after {
+        Some(method) => quote! {
+            let mut value = #ty {
+                #(#inits,)*
+            };
+            value.#method();
+            value
+        },
+        None => quote! {
+            #ty {
+                #(#inits,)*
+            }
+        },
+    }
+}
+
+/// `construct` followed by every validator, for methods that build a new
+/// value outside `new`. Returns the method's result type, `Self` or
+/// `Result<Self, String>` when anything is validated, and its body.
+fn validated_construct(
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+    inits: &[TokenStream2],
+) -> (TokenStream2, TokenStream2) {
+    let body = construct(attrs, &quote!(Self), inits);
+    match validation(attrs, fields) {
+        Some(checks) => (
+            quote!(::core::result::Result<Self, ::std::string::String>),
+            quote! {
+                let value = { #body };
+                #checks
+                ::core::result::Result::Ok(value)
+            },
+        ),
+        None => (quote!(Self), body),
+    }
+}
+
+/// `PartialEq` compares every field not marked `compare = false`, in
+/// declaration order after any listed in `eq_order`. Excluded fields need no
+/// `PartialEq` impl at all. `compare_by` replaces the fields with a single
+/// projection.
+fn eq_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    if let Some(method) = &attrs.compare_by {
+        if let Some(member) = attrs.eq_order.first() {
+            return Err(Error::new_spanned(
+                member,
+                "`eq_order` has no effect with `compare_by`",
+            ));
+        }
+        if attrs.total_float_eq {
+            return Err(Error::new_spanned(
+                method,
+                "`total_float_eq` has no effect with `compare_by`",
+            ));
+        }
+        return Ok(quote! {
+            impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+                fn eq(&self, other: &Self) -> bool {
+                    self.#method() == other.#method()
+                }
+            }
+        });
+    }
+    let compared = compared_fields(attrs, fields)?;
+    let bounds = generic_bounds(&input.generics, &compared, quote!(::core::cmp::PartialEq));
+    let where_clause = where_with(&input.generics, &bounds);
+    let eq_checks = compared.iter().map(|field| {
+        let member = &field.member;
+        if attrs.total_float_eq && is_float(field.ty) {
+            quote!(self.#member.to_bits() == other.#member.to_bits())
+        } else {
+            quote!(self.#member == other.#member)
+        }
+    });
+    let total_eq = if attrs.total_float_eq {
+        total_eq_impl(input, &compared)
+    } else {
+        TokenStream2::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                true #(&& #eq_checks)*
+            }
+        }
+
+        #total_eq
+    })
+}
+
+/// Under `total_float_eq` every compared float goes by its bits, so `Eq`
+/// holds as long as the other compared fields are `Eq` too. Those are bound
+/// whether or not they are generic, so a field like `Vec<f64>` is an error
+/// rather than a silently partial `Eq`. Bit equality does tell `0.0` and
+/// `-0.0` apart.
+fn total_eq_impl(input: &DeriveInput, compared: &[&DataclassField]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounds: Vec<_> = compared
+        .iter()
+        .filter(|field| !is_float(field.ty))
+        .map(|field| {
+            let ty = field.ty;
+            quote_spanned!(ty.span()=> #ty: ::core::cmp::Eq)
+        })
+        .collect();
+    let where_clause = where_with(&input.generics, &bounds);
+
+    quote! {
+        impl #impl_generics ::core::cmp::Eq for #name #ty_generics #where_clause {}
+    }
+}
+
+/// The fields `PartialEq` and `PartialOrd` compare: those listed in
+/// `eq_order`, then the rest not marked `compare = false`, in declaration
+/// order.
+fn compared_fields<'a, 'b>(
+    attrs: &DataclassAttrs,
+    fields: &'a [DataclassField<'b>],
+) -> syn::Result<Vec<&'a DataclassField<'b>>> {
+    let mut compared = Vec::new();
+    for (index, member) in attrs.eq_order.iter().enumerate() {
+        if attrs.eq_order[..index].contains(member) {
+            return Err(Error::new_spanned(member, "field is listed twice"));
+        }
+        let field = fields
+            .iter()
+            .find(|field| field.member == *member)
+            .ok_or_else(|| Error::new_spanned(member, "no such field"))?;
+        if !field.attrs.compare {
+            return Err(Error::new_spanned(
+                member,
+                "field is marked `compare = false`",
+            ));
+        }
+        compared.push(field);
+    }
+    compared.extend(
+        fields
+            .iter()
+            .filter(|field| field.attrs.compare && !attrs.eq_order.contains(&field.member)),
+    );
+    Ok(compared)
+}
+
+/// `order` adds a `PartialOrd` comparing the same fields as `PartialEq`,
+/// lexicographically, or the `compare_by` projection. Under `total_float_eq`
+/// floats are ordered by `total_cmp`, which agrees with their bitwise
+/// equality. An `order_desc` field
+/// is compared the other way round, so larger values sort first. `order(total)`
+/// adds an `Ord` comparing the same way, so a type that also derives `Eq` can
+/// key a `BTreeMap` or sit in a `BinaryHeap`; `compare_by`, whose key type the
+/// macro can't see, only gets `PartialOrd`.
+fn order_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    if let Some(method) = &attrs.compare_by {
+        if let Some(field) = fields.iter().find(|field| field.attrs.order_desc) {
+            return Err(Error::new_spanned(
+                &field.member,
+                "`order_desc` has no effect with `compare_by`",
+            ));
+        }
+        if attrs.order_total {
+            return Err(Error::new_spanned(
+                method,
+                "`order(total)` can't see the `compare_by` key's type; implement `Ord` by hand",
+            ));
+        }
+        return Ok(quote! {
+            impl #impl_generics ::core::cmp::PartialOrd for #name #ty_generics #where_clause {
+                fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                    ::core::cmp::PartialOrd::partial_cmp(&self.#method(), &other.#method())
+                }
+            }
+        });
+    }
+    let compared = compared_fields(attrs, fields)?;
+    let bounds = generic_bounds(&input.generics, &compared, quote!(::core::cmp::PartialOrd));
+    let where_clause = where_with(&input.generics, &bounds);
+    let comparisons = compared.iter().map(|field| {
+        let member = &field.member;
+        let (lhs, rhs) = if field.attrs.order_desc {
+            (quote!(other), quote!(self))
+        } else {
+            (quote!(self), quote!(other))
+        };
+        if attrs.total_float_eq && is_float(field.ty) {
+            quote!(::core::option::Option::Some(#lhs.#member.total_cmp(&#rhs.#member)))
+        } else {
+            quote!(::core::cmp::PartialOrd::partial_cmp(&#lhs.#member, &#rhs.#member))
+        }
+    });
+
+    let total = if attrs.order_total {
+        total_order_impl(input, attrs, &compared)
+    } else {
+        TokenStream2::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::cmp::PartialOrd for #name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                #(
+                    match #comparisons {
+                        ::core::option::Option::Some(::core::cmp::Ordering::Equal) => {}
+                        ordering => return ordering,
+                    }
+                )*
+                ::core::option::Option::Some(::core::cmp::Ordering::Equal)
+            }
+        }
+
+        #total
+    })
+}
+
+/// `order(total)` adds an `Ord` comparing the same fields in the same
+/// directions as `PartialOrd`. Each compared field is bound by `Ord`, spanned
+/// at its type, so a field that is only `PartialOrd` is reported there. `Eq`
+/// is left to the type, which usually derives it. Under `total_float_eq`,
+/// which does emit `Eq`, float fields go by `total_cmp`.
+fn total_order_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    compared: &[&DataclassField],
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let total_float = |field: &DataclassField| attrs.total_float_eq && is_float(field.ty);
+    let bounds: Vec<_> = compared
+        .iter()
+        .filter(|field| !total_float(field))
+        .map(|field| {
+            let ty = field.ty;
+            quote_spanned!(ty.span()=> #ty: ::core::cmp::Ord)
+        })
+        .collect();
+    let where_clause = where_with(&input.generics, &bounds);
+    let comparisons = compared.iter().map(|field| {
+        let member = &field.member;
+        let (lhs, rhs) = if field.attrs.order_desc {
+            (quote!(other), quote!(self))
+        } else {
+            (quote!(self), quote!(other))
+        };
+        if total_float(field) {
+            quote!(#lhs.#member.total_cmp(&#rhs.#member))
+        } else {
+            quote!(::core::cmp::Ord::cmp(&#lhs.#member, &#rhs.#member))
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::core::cmp::Ord for #name #ty_generics #where_clause {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                #(
+                    match #comparisons {
+                        ::core::cmp::Ordering::Equal => {}
+                        ordering => return ordering,
+                    }
+                )*
+                ::core::cmp::Ordering::Equal
+            }
+        }
+    }
+}
+
+/// `bounded` adds `min_value` and `max_value`, built from each field type's
+/// `MIN` and `MAX`. Only primitive numeric fields have those. Both are built
+/// like any other value, running the `freeze_after` hook and validators.
+fn bounds_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let (mins, maxes): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let ty = field.ty;
+            if !is_numeric(ty) {
+                return Err(Error::new_spanned(
+                    ty,
+                    "`bounded` needs integer or float fields, which have `MIN` and `MAX`",
+                ));
+            }
+            Ok((quote!(#member: <#ty>::MIN), quote!(#member: <#ty>::MAX)))
+        })
+        .collect::<syn::Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    let (output, min_body) = validated_construct(attrs, fields, &mins);
+    let (_, max_body) = validated_construct(attrs, fields, &maxes);
+
+    Ok(quote! {
+        /// Every field at its type's minimum.
+        pub fn min_value() -> #output {
+            #min_body
+        }
+
+        /// Every field at its type's maximum.
+        pub fn max_value() -> #output {
+            #max_body
+        }
+    })
+}
+
+/// Whether `ty` is a primitive integer or float.
+fn is_numeric(ty: &Type) -> bool {
+    const NUMERIC: &[&str] = &[
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+        "f32", "f64",
+    ];
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .is_some_and(|ident| NUMERIC.iter().any(|name| ident == name)),
+        _ => false,
+    }
+}
+
+/// Whether `ty` is spelled `f32` or `f64`.
+fn is_float(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .is_some_and(|ident| ident == "f32" || ident == "f64"),
+        _ => false,
+    }
+}
+
+/// `eq_with = "Other"` compares against another struct field by field. The
+/// exhaustive destructuring of `Other` makes any mismatch in the two field
+/// sets a compile error. Generic compared fields are bound by `PartialEq`, as
+/// in the dataclass's own `PartialEq`.
+fn eq_with_impl(input: &DeriveInput, fields: &[DataclassField], other: &Path) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let compared: Vec<_> = fields.iter().filter(|field| field.attrs.compare).collect();
+    let bounds = generic_bounds(&input.generics, &compared, quote!(::core::cmp::PartialEq));
+    let where_clause = where_with(&input.generics, &bounds);
+    let mut pattern = other.clone();
+    if let Some(last) = pattern.segments.last_mut() {
+        last.arguments = PathArguments::None;
+    }
+    let others: Vec<_> = fields
+        .iter()
+        .map(|field| format_ident!("other_{}", field.ident))
+        .collect();
+    let bindings = fields.iter().zip(&others).map(|(field, other)| {
+        let member = &field.member;
+        if field.attrs.compare {
+            quote!(#member: #other)
+        } else {
+            quote!(#member: _)
+        }
+    });
+    let eq_checks = fields
+        .iter()
+        .zip(&others)
+        .filter(|(field, _)| field.attrs.compare)
+        .map(|(field, other)| {
+            let member = &field.member;
+            quote!(self.#member == *#other)
+        });
+
+    quote! {
+        impl #impl_generics ::core::cmp::PartialEq<#other> for #name #ty_generics #where_clause {
+            fn eq(&self, other: &#other) -> bool {
+                let #pattern { #(#bindings),* } = other;
+                true #(&& #eq_checks)*
+            }
+        }
+    }
+}
+
+/// `try_new` is `new` followed by every validator, generated only when the
+/// dataclass has any.
+fn try_new_fn(attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let checks = match validation(attrs, fields) {
+        Some(checks) => checks,
+        None => return TokenStream2::new(),
+    };
+    let (params, args) = new_params(fields);
+    let allow = many_params_allow(&params);
+    let vis = constructor_vis(attrs);
+    let bounds = default_bounds(fields);
+
+    quote! {
+        /// Like `new`, but runs the field and struct validators on the result.
+        #allow
+        #vis fn try_new(#(#params),*) -> ::core::result::Result<Self, ::std::string::String>
+        where
+            #(#bounds,)*
+        {
+            let value = Self::new(#(#args),*);
+            #checks
+            ::core::result::Result::Ok(value)
+        }
+    }
+}
+
+/// `option_ctor` adds `checked_new`, a `try_new` that drops the error.
+fn checked_new_fn(attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let (params, args) = new_params(fields);
+    let allow = many_params_allow(&params);
+    let vis = constructor_vis(attrs);
+    let bounds = default_bounds(fields);
+    let body = if validation(attrs, fields).is_some() {
+        quote!(Self::try_new(#(#args),*).ok())
+    } else {
+        quote!(::core::option::Option::Some(Self::new(#(#args),*)))
+    };
+
+    quote! {
+        /// Like `new`, but returns `None` if any validator fails.
+        #allow
+        #vis fn checked_new(#(#params),*) -> ::core::option::Option<Self>
+        where
+            #(#bounds,)*
+        {
+            #body
+        }
+    }
+}
+
+/// `reset` reassigns every defaulted field from its default, leaving the
+/// required ones as they are.
+fn reset_fn(fields: &[DataclassField]) -> TokenStream2 {
+    let assignments = fields.iter().filter_map(|field| {
+        let member = &field.member;
+        let default = field.default_expr()?;
+        Some(quote!(self.#member = #default;))
+    });
+    let bounds = default_bounds(fields);
+
+    quote! {
+        /// Restores every defaulted field to its default.
+        pub fn reset(&mut self)
+        where
+            #(#bounds,)*
+        {
+            #(#assignments)*
+        }
+    }
+}
+
+/// `with` adds `with_<field>` for every field, taking `self` by value so a
+/// modified copy can be built in one expression, even on frozen dataclasses.
+/// The copy is built like any other value, so the `freeze_after` hook runs
+/// again and, when there are validators, the method returns their `Result`.
+/// A field named `field` is rejected alongside `with_field`, whose by-name
+/// updater would otherwise clash with its `with_field` method.
+fn with_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    if attrs.with_field {
+        if let Some(field) = fields.iter().find(|field| field.ident == "field") {
+            return Err(Error::new_spanned(
+                &field.member,
+                "`with` would name this field's updater `with_field`, which `with_field` already takes; rename the field or drop one of the options",
+            ));
+        }
+    }
+    let methods = fields.iter().map(|field| {
+        let name = format_ident!("with_{}", field.ident);
+        let inits: Vec<_> = fields
+            .iter()
+            .map(|other| {
+                let member = &other.member;
+                if other.member == field.member {
+                    quote!(#member: value)
+                } else {
+                    quote!(#member: self.#member)
+                }
+            })
+            .collect();
+        let (output, body) = validated_construct(attrs, fields, &inits);
+        let ty = field.ty;
+        let doc = format!(
+            "Returns this value with the `{}` field replaced.",
+            field.ident
+        );
+        quote! {
+            #[doc = #doc]
+            pub fn #name(self, value: #ty) -> #output {
+                #body
+            }
+        }
+    });
+
+    Ok(quote!(#(#methods)*))
+}
+
+/// `modified` applies `f` to a clone and rebuilds the result like any other
+/// value, so the `freeze_after` hook and validators see the change.
+fn modified_fn(attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let inits: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            quote!(#member: updated.#member)
+        })
+        .collect();
+    let (output, body) = validated_construct(attrs, fields, &inits);
+
+    quote! {
+        /// Clones this value, applies `f` to the clone and returns it,
+        /// leaving `self` unchanged.
+        pub fn modified(&self, f: impl ::core::ops::FnOnce(&mut Self)) -> #output
+        where
+            Self: ::core::clone::Clone,
+        {
+            let mut updated = ::core::clone::Clone::clone(self);
+            f(&mut updated);
+            #body
+        }
+    }
+}
+
+/// `boxed_ctor` adds `new_boxed`, for callers that immediately box the value.
+fn new_boxed_fn(attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let (params, args) = new_params(fields);
+    let allow = many_params_allow(&params);
+    let vis = constructor_vis(attrs);
+    let bounds = default_bounds(fields);
+
+    quote! {
+        /// Like `new`, but returns the value boxed.
+        #allow
+        #vis fn new_boxed(#(#params),*) -> ::std::boxed::Box<Self>
+        where
+            #(#bounds,)*
+        {
+            ::std::boxed::Box::new(Self::new(#(#args),*))
+        }
+    }
+}
+
+/// The parameters `new` takes, one per field without a default, and the
+/// matching arguments for forwarding them. An `into` field takes any
+/// `impl Into<T>`.
+fn new_params<'a>(fields: &'a [DataclassField]) -> (Vec<TokenStream2>, Vec<&'a Ident>) {
+    fields
+        .iter()
+        .filter(|field| field.attrs.default.is_none())
+        .map(|field| {
+            let ident = &field.ident;
+            let ty = field.ty;
+            let param = if field.attrs.into {
+                quote!(#ident: impl ::core::convert::Into<#ty>)
+            } else {
+                quote!(#ident: #ty)
+            };
+            (param, ident)
+        })
+        .unzip()
+}
+
+/// `private_new` leaves the positional constructors private to the struct's
+/// module, so code elsewhere has to go through the validating builder. They
+/// may then go unused, which is not worth a warning.
+fn constructor_vis(attrs: &DataclassAttrs) -> TokenStream2 {
+    if attrs.private_new {
+        quote!(#[allow(dead_code)])
+    } else {
+        quote!(pub)
+    }
+}
+
+/// Clippy flags functions taking more than seven arguments, which users can't
+/// silence on generated code, so wide constructors allow it themselves.
+fn many_params_allow(params: &[TokenStream2]) -> TokenStream2 {
+    if params.len() > 7 {
+        quote!(#[allow(clippy::too_many_arguments)])
+    } else {
+        TokenStream2::new()
+    }
+}
+
+/// Runs each field validator and then the struct-level one against `value`,
+/// returning early with the first error. `None` when nothing is validated.
+fn validation(attrs: &DataclassAttrs, fields: &[DataclassField]) -> Option<TokenStream2> {
+    let field_checks: Vec<_> = fields
+        .iter()
+        .filter_map(|field| {
+            let validate = field.attrs.validate.as_ref()?;
+            let member = &field.member;
+            Some(quote!(#validate(&value.#member)?;))
+        })
+        .collect();
+    let struct_check = attrs
+        .validate
+        .as_ref()
+        .map(|validate| quote!(value.#validate()?;));
+    if field_checks.is_empty() && struct_check.is_none() {
+        return None;
+    }
+    Some(quote! {
+        #(#field_checks)*
+        #struct_check
+    })
+}
+
+/// `const_default` spells each default in a form usable in a `const`:
+/// explicit expressions as written, and the zero value of well-known types
+/// where `Default::default()` would otherwise be called.
+fn const_default_item(fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let inits = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let value = const_default_value(field)?;
+            Ok(quote!(#member: #value))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        /// Every field at its default, usable in `const` contexts.
+        pub const EMPTY: Self = Self {
+            #(#inits,)*
+        };
+    })
+}
+
+/// The `const` zero value of a primitive, `String`, `Vec`, `Option`, or a
+/// `Cow` of `str` or a slice.
+fn const_zero(ty: &Type) -> Option<TokenStream2> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    let zero = match segment.ident.to_string().as_str() {
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => quote!(0),
+        "f32" | "f64" => quote!(0.0),
+        "bool" => quote!(false),
+        "char" => quote!('\0'),
+        "String" => quote!(::std::string::String::new()),
+        "Vec" => quote!(::std::vec::Vec::new()),
+        "Option" => quote!(::core::option::Option::None),
+        "Cow" => match type_argument(ty, "Cow")? {
+            Type::Path(path) if path.path.is_ident("str") => {
+                quote!(::std::borrow::Cow::Borrowed(""))
+            }
+            Type::Slice(_) => quote!(::std::borrow::Cow::Borrowed(&[])),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(zero)
+}
+
+/// `builder` adds `Name::builder()`, `Name::from_defaults_and(..)` and a
+/// `NameBuilder` with one `Option` slot and setter per field, named after the
+/// field behind any `builder_setter_prefix`. `build` moves the values out,
+/// filling unset defaulted fields, failing on unset required ones and running
+/// validators.
+fn builder_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> TokenStream2 {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let builder = format_ident!("{}Builder", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let build_where = where_with(&input.generics, &default_bounds(fields));
+    let generics = &input.generics;
+
+    let slots = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = field.ty;
+        quote!(#ident: ::core::option::Option<#ty>)
+    });
+    let empty = fields.iter().map(|field| {
+        let ident = &field.ident;
+        quote!(#ident: ::core::option::Option::None)
+    });
+    let setters = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = field.ty;
+        let setter = match &attrs.builder_setter_prefix {
+            Some(prefix) => format_ident!("{}{}", prefix.value(), ident),
+            None => ident.clone(),
+        };
+        let doc = format!("Sets the `{}` field.", field.ident);
+        quote! {
+            #[doc = #doc]
+            pub fn #setter(&mut self, value: #ty) -> &mut Self {
+                self.#ident = ::core::option::Option::Some(value);
+                self
+            }
+        }
+    });
+    let inits: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let ident = &field.ident;
+            match field.default_expr() {
+                Some(default) => {
+                    // Inside the builder's impl `Self` is the builder, so a
+                    // default written against the dataclass is retargeted.
+                    let default = replace_self(default, &quote!(<#name #ty_generics>));
+                    quote!(#member: self.#ident.take().unwrap_or_else(|| #default))
+                }
+                None => {
+                    let missing = format!("missing required field `{}`", field.ident);
+                    quote! {
+                        #member: self.#ident.take().ok_or_else(|| ::std::string::String::from(#missing))?
+                    }
+                }
+            }
+        })
+        .collect();
+    let body = construct(attrs, &quote!(#name), &inits);
+    let checks = validation(attrs, fields);
+    let doc = format!("Builds a [`{}`] one field at a time.", name);
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #builder #generics #where_clause {
+            #(#slots,)*
+        }
+
+        impl #impl_generics ::core::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#empty,)*
+                }
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            /// Takes the values set so far and builds the dataclass, running
+            /// its validators.
+            pub fn build(&mut self) -> ::core::result::Result<#name #ty_generics, ::std::string::String>
+            #build_where
+            {
+                let value = { #body };
+                #checks
+                ::core::result::Result::Ok(value)
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Starts a builder with no fields set.
+            pub fn builder() -> #builder #ty_generics {
+                ::core::default::Default::default()
+            }
+
+            /// Builds the dataclass from its defaults after `overrides` has
+            /// set fields on the builder.
+            pub fn from_defaults_and(
+                overrides: impl ::core::ops::FnOnce(&mut #builder #ty_generics),
+            ) -> ::core::result::Result<Self, ::std::string::String>
+            #build_where
+            {
+                let mut builder = Self::builder();
+                overrides(&mut builder);
+                builder.build()
+            }
+        }
+    }
+}
+
+/// `Debug` goes through `debug_struct`/`debug_tuple`, so `{:#?}` indents
+/// nested values. Fields marked `repr = false` are left out, and the output
+/// ends in `..` to show it. A `repr_limit = N` field prints as a list of its
+/// first `N` items, then `...` if there are more, and a `debug_with = "path"`
+/// field is handed to that function, like serde's `serialize_with`.
+fn debug_impl(input: &DeriveInput, fields: &[DataclassField]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let shown: Vec<_> = fields.iter().filter(|field| field.attrs.repr).collect();
+    // A field printed through `debug_with` needs no `Debug` of its own.
+    let (limited, whole): (Vec<_>, Vec<_>) = shown
+        .iter()
+        .filter(|field| field.attrs.debug_with.is_none())
+        .partition(|field| field.attrs.repr_limit.is_some());
+    let mut bounds = generic_bounds(&input.generics, &whole, quote!(::core::fmt::Debug));
+    // A truncated field only prints its items, so those are what need `Debug`.
+    let params: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    for field in limited
+        .iter()
+        .filter(|field| mentions_any(field.ty.to_token_stream(), &params))
+    {
+        let ty = field.ty;
+        bounds.push(quote!(for<'__item> &'__item #ty: ::core::iter::IntoIterator));
+        bounds.push(quote! {
+            for<'__item> <&'__item #ty as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug
+        });
+    }
+    let where_clause = where_with(&input.generics, &bounds);
+    let label = name.to_string();
+    let value = |field: &DataclassField| {
+        let member = &field.member;
+        if let Some(path) = &field.attrs.debug_with {
+            return quote!(&DebugWith(&self.#member, #path));
+        }
+        match field.attrs.repr_limit {
+            Some(limit) => quote!(&Truncated(&self.#member, #limit)),
+            None => quote!(&self.#member),
+        }
+    };
+    let debug_with = if shown.iter().all(|field| field.attrs.debug_with.is_none()) {
+        TokenStream2::new()
+    } else {
+        quote! {
+            struct DebugWith<'a, T: ?::core::marker::Sized>(
+                &'a T,
+                fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+            );
+
+            impl<'a, T: ?::core::marker::Sized> ::core::fmt::Debug for DebugWith<'a, T> {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
+            }
+        }
+    };
+    let truncated = if limited.is_empty() {
+        TokenStream2::new()
+    } else {
+        quote! {
+            struct Truncated<'a, C>(&'a C, usize);
+
+            impl<'a, C> ::core::fmt::Debug for Truncated<'a, C>
+            where
+                &'a C: ::core::iter::IntoIterator,
+                <&'a C as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug,
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut items = ::core::iter::IntoIterator::into_iter(self.0);
+                    let mut list = f.debug_list();
+                    list.entries(items.by_ref().take(self.1));
+                    if items.next().is_some() {
+                        list.entry(&::core::format_args!("..."));
+                    }
+                    list.finish()
+                }
+            }
+        }
+    };
+    let finish = if shown.len() < fields.len() {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    };
+    let body = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(_),
+            ..
+        }) => {
+            let entries = shown.iter().map(|field| {
+                let key = field.ident.to_string();
+                let value = value(field);
+                quote!(.field(#key, #value))
+            });
+            quote!(f.debug_struct(#label) #(#entries)* .#finish())
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(_),
+            ..
+        }) if shown.len() == fields.len() => {
+            let values = shown.iter().map(|field| value(field));
+            quote!(f.debug_tuple(#label) #(.field(#values))* .finish())
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(_),
+            ..
+        }) => {
+            // `DebugTuple` has no `finish_non_exhaustive`, so hidden
+            // positions are written as `..` in place.
+            let entries = fields.iter().map(|field| {
+                if field.attrs.repr {
+                    let value = value(field);
+                    quote!(.field(#value))
+                } else {
+                    quote!(.field(&::core::format_args!("..")))
+                }
+            });
+            quote!(f.debug_tuple(#label) #(#entries)* .finish())
+        }
+        _ => quote!(f.write_str(#label)),
+    };
+
+    quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #truncated
+                #debug_with
+                #body
+            }
+        }
+    }
+}
+
+/// `Display` writes `Name(x=1, y=2)`, or `Name(1, 2)` for a tuple struct,
+/// formatting each field with its own `Display`. Fields marked `repr = false`
+/// are left out.
+fn display_impl(input: &DeriveInput, fields: &[DataclassField]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let shown: Vec<_> = fields.iter().filter(|field| field.attrs.repr).collect();
+    let bounds = generic_bounds(&input.generics, &shown, quote!(::core::fmt::Display));
+    let where_clause = where_with(&input.generics, &bounds);
+    let named = matches!(
+        input.data,
+        Data::Struct(DataStruct {
+            fields: Fields::Named(_),
+            ..
+        })
+    );
+    let writes = shown.iter().enumerate().map(|(index, field)| {
+        let member = &field.member;
+        let separator = if index == 0 { "" } else { ", " };
+        let format = if named {
+            format!("{}{}={{}}", separator, field.ident)
+        } else {
+            format!("{}{{}}", separator)
+        };
+        quote!(::core::write!(f, #format, self.#member)?;)
+    });
+    let label = name.to_string();
+    let body = if fields.is_empty() {
+        quote!(f.write_str(#label))
+    } else {
+        quote! {
+            f.write_str(::core::concat!(#label, "("))?;
+            #(#writes)*
+            f.write_str(")")
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    }
+}
+
+/// `Hash` feeds each field not marked `hash = false` to the hasher in
+/// declaration order, after the type name under `hash(include_type)`. As with
+/// Python's `field(hash=None)`, a `compare = false` field is not hashed.
+/// `hash(name_seeded)` precedes each value with its field name. Float fields,
+/// which have no `Hash`, are hashed by their bits. Outside `total_float_eq`,
+/// where `0.0 == -0.0`, adding `0.0` first turns `-0.0` into `0.0` so the two
+/// hash alike.
+/// `hash_by(..)` instead names exactly the fields to hash, each of which must
+/// also be compared by the generated `PartialEq` so equal values hash alike.
+/// Under `compare_by` no field is compared, so neither form can be used.
+fn hash_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+    options: &HashOptions,
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let hashed: Vec<_> = if attrs.hash_by.is_empty() {
+        match &attrs.compare_by {
+            Some(method) if attrs.eq => {
+                return Err(Error::new_spanned(
+                    method,
+                    "`PartialEq` compares the `compare_by` key rather than the fields, so hashing the fields could hash equal values differently; implement `Hash` by hand",
+                ))
+            }
+            _ => {}
+        }
+        fields.iter().filter(|field| field.attrs.hash).collect()
+    } else {
+        let mut hashed = Vec::new();
+        for member in &attrs.hash_by {
+            let field = fields
+                .iter()
+                .find(|field| field.member == *member)
+                .ok_or_else(|| Error::new_spanned(member, "no such field"))?;
+            if attrs.eq && (attrs.compare_by.is_some() || !field.attrs.compare) {
+                return Err(Error::new_spanned(
+                    member,
+                    "`hash_by` field is not compared by `PartialEq`, so equal values could hash differently",
+                ));
+            }
+            hashed.push(field);
+        }
+        hashed
+    };
+    let bounds = generic_bounds(&input.generics, &hashed, quote!(::core::hash::Hash));
+    let where_clause = where_with(&input.generics, &bounds);
+    let type_tag = if options.include_type {
+        quote!(::core::hash::Hash::hash(::core::any::type_name::<Self>(), state);)
+    } else {
+        TokenStream2::new()
+    };
+    let fields = hashed.iter().map(|field| {
+        let member = &field.member;
+        let key = &field.key;
+        let seed = if options.name_seeded {
+            quote!(::core::hash::Hash::hash(#key, state);)
+        } else {
+            TokenStream2::new()
+        };
+        let value = if is_float(field.ty) && attrs.total_float_eq {
+            quote!(self.#member.to_bits())
+        } else if is_float(field.ty) {
+            quote!((self.#member + 0.0).to_bits())
+        } else {
+            quote!(self.#member)
+        };
+        quote! {
+            #seed
+            ::core::hash::Hash::hash(&#value, state);
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::hash::Hash for #name #ty_generics #where_clause {
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #type_tag
+                #(#fields)*
+            }
+        }
+    })
+}
+
+/// `Ty: Default` for each field filled from its type's `Default` impl,
+/// spanned so a missing impl is reported at the field's `default` attribute.
+fn default_bounds(fields: &[DataclassField]) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .filter(|field| option_inner(field.ty).is_none())
+        .filter_map(|field| match field.attrs.default {
+            Some(FieldDefault::Trait(span)) => {
+                let ty = field.ty;
+                Some(quote_spanned!(span=> #ty: ::core::default::Default))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// `Ty: bound` for each field whose type mentions a type parameter. Fields
+/// with concrete types need no bound, and bounding them could make a
+/// recursive struct's impl depend on itself.
+fn generic_bounds(
+    generics: &Generics,
+    fields: &[&DataclassField],
+    bound: TokenStream2,
+) -> Vec<TokenStream2> {
+    let params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    fields
+        .iter()
+        .filter(|field| mentions_any(field.ty.to_token_stream(), &params))
+        .map(|field| {
+            let ty = field.ty;
+            quote!(#ty: #bound)
+        })
+        .collect()
+}
+
+/// `tokens` with every `Self` replaced by `ty`.
+fn replace_self(tokens: TokenStream2, ty: &TokenStream2) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(ident) if ident == "Self" => ty.clone(),
+            TokenTree::Group(group) => {
+                let mut replaced = Group::new(group.delimiter(), replace_self(group.stream(), ty));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced).into()
+            }
+            token => token.into(),
+        })
+        .collect()
+}
+
+/// Whether any of `idents` appears in `tokens`. The names of lifetimes such
+/// as `'static` are not type parameters and never match.
+fn mentions_any(tokens: TokenStream2, idents: &[&Ident]) -> bool {
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        let found = match token {
+            TokenTree::Ident(ident) => idents.iter().any(|candidate| **candidate == ident),
+            TokenTree::Group(group) => mentions_any(group.stream(), idents),
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                tokens.next();
+                false
+            }
+            _ => false,
+        };
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+/// The input's where-clause extended with `extra` predicates.
+fn where_with(generics: &Generics, extra: &[TokenStream2]) -> TokenStream2 {
+    let existing = generics
+        .where_clause
+        .iter()
+        .flat_map(|clause| clause.predicates.iter());
+    quote!(where #(#existing,)* #(#extra,)*)
+}
+
+/// `T` when `ty` is spelled `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    type_argument(ty, "Option")
+}
+
+/// `T` when `ty` is spelled `Vec<T>`.
+fn vec_item(ty: &Type) -> Option<&Type> {
+    type_argument(ty, "Vec")
+}
+
+/// The first type argument, past any lifetimes, when `ty` names the generic
+/// type `outer`.
+fn type_argument<'a>(ty: &'a Type, outer: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == outer => {
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// `freeze` only holds if the getters are the sole way to reach a field: any
+/// visibility on a field would let other modules assign to it directly.
+fn check_private_fields(fields: &[DataclassField]) -> syn::Result<()> {
+    match fields
+        .iter()
+        .find(|field| !matches!(field.vis, Visibility::Inherited))
+    {
+        Some(field) => Err(syn::Error::new_spanned(
+            field.vis,
+            "`freeze` requires private fields; read them through the generated getters",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `accessors` borrows each field through a getter named after it, plus a
+/// `_mut` getter unless the dataclass is frozen. `getter_prefix` is prepended
+/// to both names, then `rename_all(getters = ..)` recases them. A
+/// `cow_getter` field also gets a `_cow` getter.
+fn accessor_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let getter = |name: String| getter_name(attrs, name);
+    let allow = getter_allow(attrs);
+
+    // A `lazy` field's own getter takes the place of the plain one.
+    let getters = fields
+        .iter()
+        .filter(|field| field.attrs.lazy.is_none())
+        .map(|field| {
+            let member = &field.member;
+            let ty = field.ty;
+            let name = getter(field.ident.to_string());
+            let doc = format!("Borrows the `{}` field.", field.ident);
+            let mut tokens = quote! {
+                #[doc = #doc]
+                #allow
+                pub fn #name(&self) -> &#ty {
+                    &self.#member
+                }
+            };
+            if !attrs.frozen {
+                let name_mut = getter(format!("{}_mut", field.ident));
+                let doc = format!("Mutably borrows the `{}` field.", field.ident);
+                tokens.extend(quote! {
+                    #[doc = #doc]
+                    #allow
+                    pub fn #name_mut(&mut self) -> &mut #ty {
+                        &mut self.#member
+                    }
+                });
+            }
+            if field.attrs.cow_getter {
+                let (borrowed, bound) = cow_target(ty).ok_or_else(|| {
+                    Error::new_spanned(ty, "`cow_getter` needs a `String` or `Vec<T>` field")
+                })?;
+                let name_cow = getter(format!("{}_cow", field.ident));
+                let doc = format!("Borrows the `{}` field as a `Cow`.", field.ident);
+                tokens.extend(quote! {
+                    #[doc = #doc]
+                    #allow
+                    pub fn #name_cow(&self) -> ::std::borrow::Cow<'_, #borrowed>
+                    where
+                        #bound
+                    {
+                        ::std::borrow::Cow::Borrowed(&self.#member[..])
+                    }
+                });
+            }
+            Ok(tokens)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote!(#(#getters)*))
+}
+
+/// A getter's name: `name` after `getter_prefix`, recased by
+/// `rename_all(getters = ..)`.
+fn getter_name(attrs: &DataclassAttrs, name: String) -> Ident {
+    let name = match &attrs.getter_prefix {
+        Some(prefix) => format!("{}{}", prefix.value(), name),
+        None => name,
+    };
+    match attrs.rename_getters {
+        Some(rule) => format_ident!("{}", rule.apply(&name)),
+        None => format_ident!("{}", name),
+    }
+}
+
+/// Recased getter names may not be snake case, which is the point.
+fn getter_allow(attrs: &DataclassAttrs) -> Option<TokenStream2> {
+    attrs
+        .rename_getters
+        .map(|_| quote!(#[allow(non_snake_case)]))
+}
+
+/// What a `cow_getter` field borrows as, `str` for a `String` and `[T]` for a
+/// `Vec<T>`, and the bound `Cow` needs to own it again: `T: Clone`, spanned
+/// at the element type so a missing impl is reported there.
+fn cow_target(ty: &Type) -> Option<(TokenStream2, Option<TokenStream2>)> {
+    if let Some(item) = vec_item(ty) {
+        let bound = quote_spanned!(item.span()=> #item: ::core::clone::Clone);
+        return Some((quote!([#item]), Some(bound)));
+    }
+    match ty {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("String") => {
+            Some((quote!(str), None))
+        }
+        _ => None,
+    }
+}
+
+/// `collection_helpers` adds `push_<field>` for each `Vec` field, appending
+/// one item and returning `&mut Self` for chaining.
+fn collection_helper_fns(fields: &[DataclassField]) -> TokenStream2 {
+    let helpers = fields.iter().filter_map(|field| {
+        let item = vec_item(field.ty)?;
+        let member = &field.member;
+        let name = format_ident!("push_{}", field.ident);
+        let doc = format!("Appends an item to the `{}` field.", field.ident);
+        Some(quote! {
+            #[doc = #doc]
+            pub fn #name(&mut self, value: #item) -> &mut Self {
+                self.#member.push(value);
+                self
+            }
+        })
+    });
+
+    quote!(#(#helpers)*)
+}
+
+/// A `lazy = "factory"` field holds a `OnceCell<T>` or `OnceLock<T>` that
+/// starts empty. Its getter, named like an accessor and replacing the field's
+/// `accessors` getter, runs the factory on first access and borrows the
+/// stored value from then on.
+fn lazy_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let allow = getter_allow(attrs);
+    let getters = fields
+        .iter()
+        .filter_map(|field| Some((field, field.attrs.lazy.as_ref()?)))
+        .map(|(field, factory)| {
+            let inner = type_argument(field.ty, "OnceCell")
+                .or_else(|| type_argument(field.ty, "OnceLock"))
+                .ok_or_else(|| {
+                    Error::new_spanned(
+                        field.ty,
+                        "`lazy` needs a `OnceCell<T>` or `OnceLock<T>` field",
+                    )
+                })?;
+            let member = &field.member;
+            let name = getter_name(attrs, field.ident.to_string());
+            let doc = format!(
+                "Returns the `{}` field, computing it on first access.",
+                field.ident
+            );
+            Ok(quote! {
+                #[doc = #doc]
+                #allow
+                pub fn #name(&self) -> &#inner {
+                    self.#member.get_or_init(#factory)
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote!(#(#getters)*))
+}
+
+/// `option_helpers` adds `set_<field>` and `clear_<field>` for each
+/// `Option` field, both returning `&mut Self` for chaining, and
+/// `take_<field>`, which moves the value out and leaves `None`.
+fn option_helper_fns(fields: &[DataclassField]) -> TokenStream2 {
+    let helpers = fields.iter().filter_map(|field| {
+        let inner = option_inner(field.ty)?;
+        let member = &field.member;
+        let set = format_ident!("set_{}", field.ident);
+        let clear = format_ident!("clear_{}", field.ident);
+        let take = format_ident!("take_{}", field.ident);
+        let set_doc = format!("Sets the `{}` field to `Some(value)`.", field.ident);
+        let clear_doc = format!("Sets the `{}` field to `None`.", field.ident);
+        let take_doc = format!(
+            "Takes the value out of the `{}` field, leaving `None`.",
+            field.ident
+        );
+        Some(quote! {
+            #[doc = #set_doc]
+            pub fn #set(&mut self, value: #inner) -> &mut Self {
+                self.#member = ::core::option::Option::Some(value);
+                self
+            }
+
+            #[doc = #clear_doc]
+            pub fn #clear(&mut self) -> &mut Self {
+                self.#member = ::core::option::Option::None;
+                self
+            }
+
+            #[doc = #take_doc]
+            pub fn #take(&mut self) -> ::core::option::Option<#inner> {
+                self.#member.take()
+            }
+        })
+    });
+
+    quote!(#(#helpers)*)
+}
+
+/// `dynamic` visits every field by name as `dyn Any`, so callers can downcast
+/// to the types they know about. Frozen dataclasses only get the shared form.
+/// `get_value` reads one field by name as a serialized `Value`, for callers
+/// that know nothing about the types, and `with_field` writes one into a copy;
+/// `skip` fields can't be reached either way.
+fn dynamic_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let types: Vec<_> = fields.iter().map(|field| field.ty).collect();
+    let visits: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            let key = &field.key;
+            quote!(f(#key, &self.#member);)
+        })
+        .collect();
+    let mut tokens = quote! {
+        /// Calls `f` with each field's name and value, in declaration order.
+        pub fn for_each_field(&self, mut f: impl ::core::ops::FnMut(&'static str, &dyn ::core::any::Any))
+        where
+            #(#types: 'static,)*
+        {
+            #(#visits)*
+        }
+    };
+    let readable: Vec<_> = fields.iter().filter(|field| !field.attrs.skip).collect();
+    let readable_types = readable.iter().map(|field| field.ty);
+    let arms = readable.iter().map(|field| {
+        let member = &field.member;
+        let key = &field.key;
+        quote! {
+            #key => ::core::option::Option::Some(
+                ::serde_value::to_value(&&self.#member).expect("field failed to serialize"),
+            )
+        }
+    });
+    // Bounding `&Ty` rather than `Ty` keeps the bounds from being checked
+    // where the struct is defined, so a dataclass whose fields can't be
+    // serialized still gets the `dyn Any` visitors.
+    tokens.extend(quote! {
+        /// Serializes the field called `name`, or returns `None` if there is
+        /// no such field.
+        pub fn get_value(&self, name: &str) -> ::core::option::Option<::serde_value::Value>
+        where
+            #(for<'__field> &'__field #readable_types: ::serde::Serialize,)*
+        {
+            match name {
+                #(#arms,)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    });
+    if attrs.with_field {
+        tokens.extend(with_field_fn(attrs, fields, &readable));
+    }
+    if !attrs.frozen {
+        let visits = fields.iter().map(|field| {
+            let member = &field.member;
+            let key = &field.key;
+            quote!(f(#key, &mut self.#member);)
+        });
+        tokens.extend(quote! {
+            /// Calls `f` with each field's name and a mutable reference to its
+            /// value, in declaration order.
+            pub fn for_each_field_mut(&mut self, mut f: impl ::core::ops::FnMut(&'static str, &mut dyn ::core::any::Any))
+            where
+                #(#types: 'static,)*
+            {
+                #(#visits)*
+            }
+        });
+    }
+    tokens
+}
+
+/// `with_field` clones the value, deserializes a `Value` into the field
+/// called `name` and rebuilds the result like any other value, so the
+/// `freeze_after` hook and validators run on it.
+fn with_field_fn(
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+    writable: &[&DataclassField],
+) -> TokenStream2 {
+    let types = writable.iter().map(|field| field.ty);
+    let arms = writable.iter().map(|field| {
+        let member = &field.member;
+        let key = &field.key;
+        quote! {
+            #key => {
+                updated.#member = ::serde_value::Value::deserialize_into(value)
+                    .map_err(|error| ::std::format!("field `{}`: {}", name, error))?
+            }
+        }
+    });
+    let inits: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            quote!(#member: updated.#member)
+        })
+        .collect();
+    let body = construct(attrs, &quote!(Self), &inits);
+    let checks = validation(attrs, fields);
+
+    quote! {
+        /// Returns a copy with the field called `name` replaced by `value`,
+        /// deserialized into the field's type. Fails on an unknown name, a
+        /// value of the wrong shape or a failed validator.
+        pub fn with_field(
+            &self,
+            name: &str,
+            value: ::serde_value::Value,
+        ) -> ::core::result::Result<Self, ::std::string::String>
+        where
+            Self: ::core::clone::Clone,
+            #(for<'de> #types: ::serde::Deserialize<'de>,)*
+        {
+            let mut updated = ::core::clone::Clone::clone(self);
+            match name {
+                #(#arms,)*
+                _ => return ::core::result::Result::Err(::std::format!("no field named `{}`", name)),
+            }
+            let value = { #body };
+            #checks
+            ::core::result::Result::Ok(value)
+        }
+    }
+}
+
+/// `iter_fields` yields each represented field lazily, serializing a field
+/// only when the iterator reaches it.
+fn iter_fields_fn(fields: &[DataclassField]) -> TokenStream2 {
+    let shown = represented(fields);
+    let count = shown.len();
+    let types = shown.iter().map(|field| field.ty);
+    let arms = shown.iter().enumerate().map(|(index, field)| {
+        let member = &field.member;
+        let key = &field.key;
+        quote! {
+            #index => (#key, ::serde_value::to_value(&self.#member).expect("field failed to serialize"))
+        }
+    });
+
+    quote! {
+        /// Iterates over `(name, value)` pairs in declaration order.
+        pub fn iter_fields(&self) -> impl ::core::iter::Iterator<Item = (&'static str, ::serde_value::Value)> + '_
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            (0..#count).map(move |index| match index {
+                #(#arms,)*
+                _ => ::core::unreachable!(),
+            })
+        }
+    }
+}
+
+/// The fields `iter_fields` and `as_dict` serialize: all but `repr = false`
+/// and `skip`.
+fn represented<'a, 'b>(fields: &'a [DataclassField<'b>]) -> Vec<&'a DataclassField<'b>> {
+    fields
+        .iter()
+        .filter(|field| field.attrs.repr && !field.attrs.skip)
+        .collect()
+}
+
+/// `as_dict` serializes the represented fields into a map keyed by field
+/// name. `as_ordered_dict` returns the same pairs in declaration order, for
+/// output that must be reproducible, and `as_kwargs` repeats it for Python
+/// bridges rebuilding the value from keyword arguments. A field's
+/// `skip_serializing_if` predicate drops it from all of them, and a `flatten`
+/// field, itself an `as_dict` dataclass, contributes its own entries in place
+/// of one nested value.
+/// `diff_dict` compares the same fields, minus those marked `compare = false`,
+/// by their serialized values, for change tracking.
+fn as_dict_fns(fields: &[DataclassField]) -> TokenStream2 {
+    let shown = represented(fields);
+    let types: Vec<_> = shown
+        .iter()
+        .filter(|field| !field.attrs.flatten)
+        .map(|field| field.ty)
+        .collect();
+    let count = shown.len();
+    let pushes = shown.iter().map(|field| {
+        let member = &field.member;
+        let key = &field.key;
+        let push = if field.attrs.flatten {
+            // Spanned so a field that isn't an `as_dict` dataclass is
+            // reported at the field.
+            let ty = field.ty;
+            quote_spanned!(ty.span()=> dict.extend(<#ty>::as_ordered_dict(&self.#member));)
+        } else {
+            quote! {
+                dict.push((#key, ::serde_value::to_value(&self.#member).expect("field failed to serialize")));
+            }
+        };
+        match &field.attrs.skip_serializing_if {
+            Some(predicate) => quote! {
+                if !#predicate(&self.#member) {
+                    #push
+                }
+            },
+            None => push,
+        }
+    });
+    let diffs = shown.iter().filter(|field| field.attrs.compare).map(|field| {
+        let member = &field.member;
+        let key = &field.key;
+        if field.attrs.flatten {
+            let ty = field.ty;
+            return quote_spanned!(ty.span()=> diff.extend(<#ty>::diff_dict(&self.#member, &other.#member));)
+        }
+        quote! {
+            let old = ::serde_value::to_value(&self.#member).expect("field failed to serialize");
+            let new = ::serde_value::to_value(&other.#member).expect("field failed to serialize");
+            if old != new {
+                diff.insert(#key, (old, new));
+            }
+        }
+    });
+
+    quote! {
+        /// Serializes each field into a `(name, value)` pair, in declaration
+        /// order, leaving out those whose `skip_serializing_if` holds.
+        pub fn as_ordered_dict(&self) -> ::std::vec::Vec<(&'static str, ::serde_value::Value)>
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            let mut dict = ::std::vec::Vec::with_capacity(#count);
+            #(#pushes)*
+            dict
+        }
+
+        /// The same pairs as `as_ordered_dict`, shaped for passing as keyword
+        /// arguments when rebuilding the value on the other side of a Python
+        /// bridge.
+        pub fn as_kwargs(&self) -> ::std::vec::Vec<(&'static str, ::serde_value::Value)>
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            self.as_ordered_dict()
+        }
+
+        /// Serializes each field into a map from its name to its value.
+        pub fn as_dict(&self) -> ::std::collections::HashMap<::std::string::String, ::serde_value::Value>
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            self.as_ordered_dict()
+                .into_iter()
+                .map(|(key, value)| (::std::string::String::from(key), value))
+                .collect()
+        }
+
+        /// Maps the name of each field that differs between `self` and
+        /// `other` to its serialized `(old, new)` values.
+        pub fn diff_dict(
+            &self,
+            other: &Self,
+        ) -> ::std::collections::HashMap<&'static str, (::serde_value::Value, ::serde_value::Value)>
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            let mut diff = ::std::collections::HashMap::new();
+            #(#diffs)*
+            diff
+        }
+    }
+}
+
+/// `as_vec` serializes every field but the `skip`ped ones, in declaration
+/// order, and `try_from_values` reverses it for row-oriented formats like CSV.
+/// Skipped fields are refilled from their defaults.
+fn as_vec_fns(attrs: &DataclassAttrs, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let positional: Vec<_> = fields.iter().filter(|field| !field.attrs.skip).collect();
+    let count = positional.len();
+    let types: Vec<_> = positional.iter().map(|field| field.ty).collect();
+    let members = positional.iter().map(|field| &field.member);
+    let inits = fields
+        .iter()
+        .map(|field| {
+            let member = &field.member;
+            if !field.attrs.skip {
+                let error = format!("field `{}`: {{}}", field.ident);
+                return Ok(quote! {
+                    #member: values
+                        .next()
+                        .unwrap()
+                        .deserialize_into()
+                        .map_err(|error| ::std::format!(#error, error))?
+                });
+            }
+            let default = field.default_expr().ok_or_else(|| {
+                Error::new_spanned(
+                    &field.ident,
+                    "`as_vec` needs a default for `skip` fields to rebuild them",
+                )
+            })?;
+            Ok(quote!(#member: #default))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let body = construct(attrs, &quote!(Self), &inits);
+    let checks = validation(attrs, fields);
+    let bounds = default_bounds(fields);
+
+    Ok(quote! {
+        /// Serializes each field into a value, in declaration order.
+        pub fn as_vec(&self) -> ::std::vec::Vec<::serde_value::Value>
+        where
+            #(#types: ::serde::Serialize,)*
+        {
+            ::std::vec![#(::serde_value::to_value(&self.#members).expect("field failed to serialize")),*]
+        }
+
+        /// Builds the dataclass from values in declaration order, as produced
+        /// by `as_vec`, running its validators.
+        pub fn try_from_values(
+            values: ::std::vec::Vec<::serde_value::Value>,
+        ) -> ::core::result::Result<Self, ::std::string::String>
+        where
+            #(#types: ::serde::de::DeserializeOwned,)*
+            #(#bounds,)*
+        {
+            if values.len() != #count {
+                return ::core::result::Result::Err(::std::format!(
+                    "expected {} values, got {}",
+                    #count,
+                    values.len()
+                ));
+            }
+            let mut values = values.into_iter();
+            let value = { #body };
+            #checks
+            ::core::result::Result::Ok(value)
+        }
+    })
+}
+
+/// `schema` is a lightweight self-description for tooling: the struct name
+/// plus each field's name, source type and whether it has a default. Under
+/// `namespace` the name is qualified as `namespace.Name`. `schema_hash`,
+/// also only emitted under `schema`, is a 64-bit FNV-1a hash of the field
+/// names and types. The types are hashed as `type_signature` spells them
+/// rather than as the compiler prints them, so the hash is the same on every
+/// toolchain and caches can store it next to serialized values.
+fn schema_fn(name: &Ident, attrs: &DataclassAttrs, fields: &[DataclassField]) -> TokenStream2 {
+    let name = match &attrs.namespace {
+        Some(namespace) => format!("{}.{}", namespace.value(), name),
+        None => name.to_string(),
+    };
+    let schema_hash = fields.iter().fold(FNV_OFFSET_BASIS, |hash, field| {
+        let mut ty = String::new();
+        type_signature(field.ty.to_token_stream(), &mut ty);
+        // A separator byte that can't appear in either string keeps
+        // `("ab", "c")` and `("a", "bc")` apart.
+        [field.key.as_bytes(), &[0], ty.as_bytes(), &[0]]
+            .concat()
+            .iter()
+            .fold(hash, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+            })
+    });
+    let entries = fields.iter().map(|field| {
+        let key = &field.key;
+        let ty = field.ty;
+        let has_default = field.attrs.default.is_some();
+        quote! {
+            {
+                "name": #key,
+                "type": ::core::stringify!(#ty),
+                "has_default": #has_default,
+            }
+        }
+    });
+
+    quote! {
+        /// Describes this dataclass's fields as JSON.
+        pub fn schema() -> ::serde_json::Value {
+            ::serde_json::json!({
+                "name": #name,
+                "fields": [#(#entries),*],
+            })
+        }
+
+        /// A hash of the field names and types, computed when the dataclass
+        /// is compiled, that changes whenever its shape does. Emitted with
+        /// `schema`.
+        pub const fn schema_hash() -> u64 {
+            #schema_hash
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Spells a type without relying on how `proc_macro2` spaces tokens when
+/// printing them: punctuation and delimiters are written bare, and a single
+/// space only separates adjacent words, as in `Vec<Option<u8>>` or
+/// `&'static str`.
+fn type_signature(tokens: TokenStream2, out: &mut String) {
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                type_signature(group.stream(), out);
+                out.push_str(close);
+            }
+            TokenTree::Punct(punct) => out.push(punct.as_char()),
+            word => {
+                if out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    out.push(' ');
+                }
+                out.push_str(&word.to_string());
+            }
+        }
+    }
+}
+
+/// `borrow` lets a single-field wrapper stand in for its inner value's
+/// borrowed form, e.g. looking up `HashMap<UserName, _>` with a `&str`.
+/// `Borrow` promises the wrapper hashes like what it borrows as, so a hash
+/// that mixes in the type or field name is rejected. `option` names the
+/// option asking for the impl, for the errors.
+fn borrow_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+    target: Option<&Type>,
+    option: &str,
+) -> syn::Result<TokenStream2> {
+    let mixed_in = attrs.hash.as_ref().and_then(|options| {
+        if options.include_type {
+            Some("include_type")
+        } else if options.name_seeded {
+            Some("name_seeded")
+        } else {
+            None
+        }
+    });
+    if let Some(mixed_in) = mixed_in {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!(
+                "`{}` needs the wrapper to hash like its inner value; drop `{}`",
+                option, mixed_in
+            ),
+        ));
+    }
+    let field = match fields {
+        [field] => field,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                format!("`{}` requires a struct with exactly one field", option),
+            ))
+        }
+    };
+    let target = match target {
+        Some(target) => target.clone(),
+        None => borrowed_type(field.ty).ok_or_else(|| {
+            Error::new_spanned(
+                field.ty,
+                "cannot infer the borrowed type; name it with `#[dataclass(borrow = \"...\")]`",
+            )
+        })?,
+    };
+
+    let name = &input.ident;
+    let member = &field.member;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::borrow::Borrow<#target> for #name #ty_generics #where_clause {
+            fn borrow(&self) -> &#target {
+                ::core::borrow::Borrow::borrow(&self.#member)
+            }
+        }
+    })
+}
+
+/// `from_str` lets a single-field wrapper parse the way its inner value
+/// does, reporting the inner type's error.
+fn from_str_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let field = match fields {
+        [field] => field,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`from_str` requires a struct with exactly one field",
+            ))
+        }
+    };
+    let name = &input.ident;
+    let member = &field.member;
+    let ty = field.ty;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = where_with(&input.generics, &[quote!(#ty: ::core::str::FromStr)]);
+    let body = construct(
+        attrs,
+        &quote!(Self),
+        &[quote!(#member: ::core::str::FromStr::from_str(s)?)],
+    );
+
+    Ok(quote! {
+        impl #impl_generics ::core::str::FromStr for #name #ty_generics #where_clause {
+            type Err = <#ty as ::core::str::FromStr>::Err;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                ::core::result::Result::Ok({ #body })
+            }
+        }
+    })
+}
+
+/// `from_field` converts a struct's one required field into the whole
+/// struct, defaulting the rest through `new`.
+fn from_field_impl(input: &DeriveInput, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|field| field.attrs.default.is_none())
+        .collect();
+    let field = match required.as_slice() {
+        [field] => field,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`from_field` requires exactly one field without a default",
+            ))
+        }
+    };
+    let name = &input.ident;
+    let ty = field.ty;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = where_with(&input.generics, &default_bounds(fields));
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+            fn from(value: #ty) -> Self {
+                Self::new(value)
+            }
+        }
+    })
+}
+
+/// `try_into_tuple` converts a struct into a tuple of its fields in
+/// declaration order, failing with the first validator error instead.
+fn try_into_tuple_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let types = fields.iter().map(|field| field.ty);
+    let members = fields.iter().map(|field| &field.member);
+    let checks = validation(attrs, fields);
+
+    quote! {
+        impl #impl_generics ::core::convert::TryFrom<#name #ty_generics> for (#(#types,)*) #where_clause {
+            type Error = ::std::string::String;
+
+            fn try_from(value: #name #ty_generics) -> ::core::result::Result<Self, Self::Error> {
+                #checks
+                ::core::result::Result::Ok((#(value.#members,)*))
+            }
+        }
+    }
+}
+
+/// `extends = "Base"` projects a struct down to the dataclass it extends,
+/// moving the fields marked `base` into a `Base` literal. The literal is
+/// spanned at `Base` and each field at its own, so the compiler reports a
+/// `base` field `Base` lacks, or a `Base` field left unmarked, where it is.
+fn extends_impl(
+    input: &DeriveInput,
+    base: &Type,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let shared: Vec<_> = fields.iter().filter(|field| field.attrs.base).collect();
+    if shared.is_empty() {
+        return Err(Error::new_spanned(
+            base,
+            "`extends` needs the shared fields marked `#[dataclass(base)]`",
+        ));
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let inits = shared.iter().map(|field| {
+        let member = &field.member;
+        quote_spanned!(member.span()=> #member: value.#member)
+    });
+    let literal = quote_spanned!(base.span()=> Self { #(#inits,)* });
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for #base #where_clause {
+            fn from(value: #name #ty_generics) -> Self {
+                #literal
+            }
+        }
+    })
+}
+
+/// `ref_view` adds `NameRef<'a>`, mirroring the struct's fields as `&'a`
+/// references, and a `From<&'a Name>` that borrows every field at once.
+fn ref_view_impl(input: &DeriveInput, fields: &[DataclassField]) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let view = format_ident!("{}Ref", name);
+    let lifetime: Lifetime = parse_quote!('a);
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            name,
+            "`ref_view` requires a struct with at least one field",
+        ));
+    }
+    if input
+        .generics
+        .lifetimes()
+        .any(|param| param.lifetime == lifetime)
+    {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`ref_view` names its borrow `'a`; rename the struct's own `'a` lifetime",
+        ));
+    }
+
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut view_generics = input.generics.clone();
+    view_generics.params.insert(0, parse_quote!(#lifetime));
+    let (impl_generics, view_ty_generics, _) = view_generics.split_for_impl();
+
+    let types = fields.iter().map(|field| {
+        let vis = field.vis;
+        let ty = field.ty;
+        match &field.member {
+            Member::Named(ident) => quote!(#vis #ident: &#lifetime #ty),
+            Member::Unnamed(_) => quote!(#vis &#lifetime #ty),
+        }
+    });
+    let body = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(_),
+            ..
+        }) => quote!(#where_clause { #(#types,)* }),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(_),
+            ..
+        }) => quote!((#(#types,)*) #where_clause;),
+        _ => unreachable!("unit structs are rejected above"),
+    };
+    let inits = fields.iter().map(|field| {
+        let member = &field.member;
+        quote!(#member: &value.#member)
+    });
+    let doc = format!("Borrows every field of a [`{}`].", name);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #view #view_generics #body
+
+        impl #impl_generics ::core::convert::From<&#lifetime #name #ty_generics> for #view #view_ty_generics #where_clause {
+            fn from(value: &#lifetime #name #ty_generics) -> Self {
+                #view {
+                    #(#inits,)*
+                }
+            }
+        }
+    })
+}
+
+/// `equivalent` lets a single-field wrapper be looked up by its inner value,
+/// in std maps through `Borrow` and in `hashbrown`/`indexmap` through their
+/// blanket `Equivalent` impl. The generated `Hash` and `PartialEq` already
+/// match the inner value's, which `borrow_impl` checks.
+fn equivalent_impl(
+    input: &DeriveInput,
+    attrs: &DataclassAttrs,
+    fields: &[DataclassField],
+) -> syn::Result<TokenStream2> {
+    let target = fields.first().map(|field| field.ty);
+    borrow_impl(input, attrs, fields, target, "equivalent")
+}
+
+/// The well-known owned-to-borrowed pairs: `String -> str`, `Vec<T> -> [T]`.
+fn borrowed_type(ty: &Type) -> Option<Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+    match (segment.ident.to_string().as_str(), &segment.arguments) {
+        ("String", PathArguments::None) => Some(parse_quote!(str)),
+        ("Vec", PathArguments::AngleBracketed(args)) => match args.args.first()? {
+            GenericArgument::Type(item) => Some(parse_quote!([#item])),
+            _ => None,
+        },
+        _ => None,
+    }
+}
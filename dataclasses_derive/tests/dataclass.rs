@@ -0,0 +1,2650 @@
+#![allow(dead_code)]
+
+use dataclasses_derive::Dataclass;
+use serde_value::Value;
+
+#[test]
+fn iter_fields_yields_declaration_order() {
+    #[derive(Dataclass)]
+    #[dataclass(iter_fields)]
+    struct Person {
+        name: String,
+        #[dataclass(repr = false)]
+        password: String,
+        age: u32,
+        #[dataclass(skip)]
+        cache: Vec<u8>,
+    }
+
+    let person = Person {
+        name: "Alice".to_string(),
+        password: "hunter2".to_string(),
+        age: 30,
+        cache: vec![1, 2, 3],
+    };
+
+    let fields: Vec<_> = person.iter_fields().collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("name", Value::String("Alice".to_string())),
+            ("age", Value::U32(30)),
+        ]
+    );
+}
+
+mod isolated {
+    use dataclasses_derive::Dataclass;
+
+    // Local items that would capture any relative path in the expansion.
+    mod serde {}
+    mod serde_value {}
+    struct Iterator;
+
+    #[derive(Dataclass)]
+    #[dataclass(iter_fields)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+}
+
+#[test]
+fn derive_uses_absolute_paths() {
+    let point = isolated::Point { x: 1, y: 2 };
+    let fields: Vec<_> = point.iter_fields().collect();
+    assert_eq!(fields, vec![("x", Value::I32(1)), ("y", Value::I32(2))]);
+}
+
+#[test]
+fn schema_lists_fields() {
+    #[derive(Dataclass)]
+    #[dataclass(schema)]
+    struct Config {
+        host: String,
+        #[dataclass(default = "8080")]
+        port: u16,
+        #[dataclass(default_factory = "u8::max_value")]
+        retries: u8,
+    }
+
+    assert_eq!(
+        Config::schema(),
+        serde_json::json!({
+            "name": "Config",
+            "fields": [
+                { "name": "host", "type": "String", "has_default": false },
+                { "name": "port", "type": "u16", "has_default": true },
+                { "name": "retries", "type": "u8", "has_default": true },
+            ],
+        })
+    );
+}
+
+#[test]
+fn new_takes_required_fields_in_declaration_order() {
+    #[derive(Dataclass)]
+    struct Account {
+        owner: String,
+        #[dataclass(default = "100")]
+        balance: i64,
+        currency: String,
+        #[dataclass(default)]
+        history: Vec<i64>,
+    }
+
+    let account = Account::new("Alice".to_string(), "EUR".to_string());
+    assert_eq!(account.owner, "Alice");
+    assert_eq!(account.balance, 100);
+    assert_eq!(account.currency, "EUR");
+    assert!(account.history.is_empty());
+}
+
+#[test]
+fn borrow_looks_up_wrapper_keys_by_inner_value() {
+    use std::collections::HashMap;
+
+    #[derive(Dataclass, Hash, PartialEq, Eq)]
+    #[dataclass(borrow, eq = false)]
+    struct UserName(String);
+
+    #[derive(Dataclass, Hash, PartialEq, Eq)]
+    #[dataclass(borrow = "[u8]", eq = false)]
+    struct Bytes {
+        data: Vec<u8>,
+    }
+
+    let mut ages = HashMap::new();
+    ages.insert(UserName::new("alice".to_string()), 30);
+    assert_eq!(ages.get("alice"), Some(&30));
+
+    let mut sizes = HashMap::new();
+    sizes.insert(Bytes::new(vec![1, 2]), 2);
+    assert_eq!(sizes.get(&[1u8, 2][..]), Some(&2));
+}
+
+#[test]
+fn default_option_is_none_without_inner_default() {
+    struct NotDefault;
+
+    #[derive(Dataclass)]
+    #[dataclass(eq = false)]
+    struct Slot<T> {
+        #[dataclass(default)]
+        value: Option<T>,
+        #[dataclass(default)]
+        fallback: Option<NotDefault>,
+    }
+
+    let slot = Slot::<NotDefault>::default();
+    assert!(slot.value.is_none());
+    assert!(slot.fallback.is_none());
+    assert!(Slot::<NotDefault>::new().value.is_none());
+}
+
+#[test]
+fn freeze_after_runs_once_fields_are_set() {
+    #[derive(Dataclass)]
+    #[dataclass(frozen, freeze_after = "seal")]
+    struct Packet {
+        payload: Vec<u8>,
+        #[dataclass(default)]
+        checksum: u32,
+    }
+
+    impl Packet {
+        fn seal(&mut self) {
+            self.checksum = self.payload.iter().map(|&byte| u32::from(byte)).sum();
+        }
+    }
+
+    assert_eq!(Packet::new(vec![1, 2, 3]).checksum, 6);
+}
+
+mod accounts {
+    use dataclasses_derive::Dataclass;
+
+    #[derive(Dataclass)]
+    pub struct User {
+        pub name: String,
+        password_hash: u64,
+        #[dataclass(default)]
+        pub(crate) logins: u32,
+    }
+
+    impl User {
+        pub fn password_hash(&self) -> u64 {
+            self.password_hash
+        }
+    }
+}
+
+#[test]
+fn new_constructs_private_fields_from_outside() {
+    let user = accounts::User::new("alice".to_string(), 42);
+    assert_eq!(user.name, "alice");
+    assert_eq!(user.password_hash(), 42);
+    assert_eq!(user.logins, 0);
+}
+
+#[test]
+fn eq_with_compares_structurally_identical_types() {
+    #[derive(Dataclass)]
+    struct Celsius {
+        degrees: i32,
+        station: String,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(eq_with = "Celsius")]
+    struct Reading {
+        degrees: i32,
+        station: String,
+    }
+
+    let reading = Reading::new(21, "roof".to_string());
+    assert!(reading == Celsius::new(21, "roof".to_string()));
+    assert!(reading != Celsius::new(22, "roof".to_string()));
+    assert!(reading == Reading::new(21, "roof".to_string()));
+
+    #[derive(Dataclass)]
+    struct Sample<T> {
+        value: T,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(eq_with = "Sample<T>")]
+    struct Measured<T> {
+        value: T,
+    }
+
+    assert!(Measured::new(1.5) == Sample::new(1.5));
+    assert!(Measured::new("a") != Sample::new("b"));
+}
+
+#[test]
+fn tuple_fields_take_implicit_and_explicit_defaults() {
+    #[derive(Dataclass)]
+    struct Pairs<T> {
+        #[dataclass(default)]
+        implicit: (i32, String),
+        #[dataclass(default = "(7, String::from(\"seven\"))")]
+        explicit: (i32, String),
+        #[dataclass(default)]
+        generic: (T, bool),
+    }
+
+    let pairs = Pairs::<u8>::default();
+    assert_eq!(pairs.implicit, (0, String::new()));
+    assert_eq!(pairs.explicit, (7, "seven".to_string()));
+    assert_eq!(pairs.generic, (0, false));
+}
+
+#[test]
+fn rename_all_getters_recases_accessors() {
+    #[derive(Dataclass)]
+    #[dataclass(
+        accessors,
+        iter_fields,
+        rename_all(keys = "PascalCase", getters = "camelCase")
+    )]
+    struct Contact {
+        first_name: String,
+        last_name: String,
+    }
+
+    let mut contact = Contact::new("Ada".to_string(), "Lovelace".to_string());
+    contact.lastNameMut().push_str(" King");
+    assert_eq!(contact.firstName(), "Ada");
+    assert_eq!(contact.lastName(), "Lovelace King");
+
+    let keys: Vec<_> = contact.iter_fields().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["FirstName", "LastName"]);
+}
+
+#[test]
+fn builder_starts_from_default() {
+    #[derive(Dataclass)]
+    #[dataclass(builder)]
+    struct Server {
+        host: String,
+        #[dataclass(default = "8080")]
+        port: u16,
+    }
+
+    let server = ServerBuilder::default()
+        .host("localhost".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+
+    let missing = Server::builder().port(1).build();
+    assert_eq!(missing.err().unwrap(), "missing required field `host`");
+}
+
+#[test]
+fn const_default_uses_const_constructors() {
+    #[derive(Dataclass)]
+    #[dataclass(const_default)]
+    struct Record {
+        #[dataclass(default)]
+        label: String,
+        #[dataclass(default)]
+        values: Vec<u8>,
+        count: i32,
+        #[dataclass(default = "3")]
+        retries: u8,
+    }
+
+    const EMPTY: Record = Record::EMPTY;
+    assert_eq!(EMPTY.label, "");
+    assert!(EMPTY.values.is_empty());
+    assert_eq!(EMPTY.count, 0);
+    assert_eq!(EMPTY.retries, 3);
+}
+
+#[test]
+fn struct_validator_checks_cross_field_invariants() {
+    fn non_empty(label: &str) -> Result<(), String> {
+        if label.is_empty() {
+            Err("label must not be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(validate = "check_invariants")]
+    struct Span {
+        #[dataclass(validate = "non_empty")]
+        label: String,
+        start: u32,
+        end: u32,
+    }
+
+    impl Span {
+        fn check_invariants(&self) -> Result<(), String> {
+            if self.start < self.end {
+                Ok(())
+            } else {
+                Err(format!(
+                    "start {} must precede end {}",
+                    self.start, self.end
+                ))
+            }
+        }
+    }
+
+    assert!(Span::try_new("ok".to_string(), 1, 2).is_ok());
+    assert_eq!(
+        Span::try_new("".to_string(), 1, 2).err().unwrap(),
+        "label must not be empty"
+    );
+    assert_eq!(
+        Span::try_new("bad".to_string(), 5, 2).err().unwrap(),
+        "start 5 must precede end 2"
+    );
+}
+
+#[test]
+fn checked_new_returns_none_on_invalid_input() {
+    fn positive(value: &i32) -> Result<(), String> {
+        if *value > 0 {
+            Ok(())
+        } else {
+            Err("must be positive".to_string())
+        }
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(option_ctor)]
+    struct Quantity {
+        #[dataclass(validate = "positive")]
+        amount: i32,
+    }
+
+    assert_eq!(Quantity::checked_new(3).map(|q| q.amount), Some(3));
+    assert!(Quantity::checked_new(-1).is_none());
+}
+
+#[test]
+fn smart_pointer_defaults_bound_the_pointee() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Dataclass)]
+    struct Shared<T> {
+        #[dataclass(default)]
+        boxed: Box<T>,
+        #[dataclass(default)]
+        counted: Rc<T>,
+        #[dataclass(default)]
+        atomic: Arc<T>,
+    }
+
+    let shared = Shared::<Vec<u8>>::default();
+    assert!(shared.boxed.is_empty());
+    assert!(shared.counted.is_empty());
+    assert!(shared.atomic.is_empty());
+    assert_eq!(*Shared::<u32>::new().boxed, 0);
+}
+
+#[test]
+fn hash_include_type_separates_identical_values() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    #[derive(Dataclass)]
+    #[dataclass(hash(include_type))]
+    struct UserId {
+        id: u64,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(hash(include_type))]
+    struct OrderId {
+        id: u64,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(hash)]
+    struct Plain {
+        id: u64,
+        #[dataclass(hash = false)]
+        note: String,
+    }
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // A map keyed by hash alone, as a heterogeneous cache might use.
+    let keys: HashSet<u64> = vec![hash_of(&UserId::new(7)), hash_of(&OrderId::new(7))]
+        .into_iter()
+        .collect();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(
+        hash_of(&Plain::new(7, "a".to_string())),
+        hash_of(&Plain::new(7, "b".to_string()))
+    );
+}
+
+#[test]
+fn default_expr_sees_user_where_clause() {
+    #[derive(Dataclass)]
+    struct Slot<T>
+    where
+        T: Default,
+    {
+        label: &'static str,
+        #[dataclass(default = "T::default()")]
+        value: T,
+    }
+
+    let slot = Slot::<Vec<u8>>::new("empty");
+    assert_eq!(slot.label, "empty");
+    assert!(slot.value.is_empty());
+    assert_eq!(Slot::<u32>::new("zero").value, 0);
+}
+
+#[test]
+fn ref_view_borrows_each_field() {
+    #[derive(Dataclass)]
+    #[dataclass(ref_view)]
+    struct Document<T> {
+        title: String,
+        pages: Vec<T>,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(ref_view)]
+    struct Pair(u8, String);
+
+    fn title_len(view: DocumentRef<'_, u32>) -> usize {
+        view.title.len()
+    }
+
+    let document = Document::new("Guide".to_string(), vec![1, 2, 3]);
+    let view = DocumentRef::from(&document);
+    assert_eq!(view.title, "Guide");
+    assert_eq!(view.pages, &[1, 2, 3]);
+    assert_eq!(title_len((&document).into()), 5);
+
+    let pair = Pair::new(1, "one".to_string());
+    let view: PairRef = (&pair).into();
+    assert_eq!((*view.0, view.1.as_str()), (1, "one"));
+}
+
+#[test]
+fn enum_default_builds_designated_variant() {
+    #[derive(Dataclass, Debug)]
+    enum Shape {
+        Point,
+        #[dataclass(default)]
+        Circle {
+            #[dataclass(default = "1.0")]
+            radius: f64,
+            label: String,
+        },
+        Square(f64),
+    }
+
+    assert_eq!(
+        Shape::default(),
+        Shape::Circle {
+            radius: 1.0,
+            label: String::new(),
+        }
+    );
+    assert_eq!(Shape::Square(2.0), Shape::Square(2.0));
+    assert_ne!(Shape::Point, Shape::Square(0.0));
+}
+
+#[test]
+fn collection_helpers_push_onto_vec_fields() {
+    #[derive(Dataclass)]
+    #[dataclass(collection_helpers)]
+    struct Playlist {
+        name: String,
+        #[dataclass(default)]
+        tracks: Vec<String>,
+        #[dataclass(default)]
+        ratings: Vec<u8>,
+    }
+
+    let mut playlist = Playlist::new("road trip".to_string());
+    playlist
+        .push_tracks("Intro".to_string())
+        .push_tracks("Outro".to_string())
+        .push_ratings(5);
+    assert_eq!(playlist.tracks, ["Intro", "Outro"]);
+    assert_eq!(playlist.ratings, [5]);
+}
+
+#[test]
+fn as_ordered_dict_keeps_declaration_order() {
+    #[derive(Dataclass)]
+    #[dataclass(as_dict)]
+    struct Record {
+        zeta: u8,
+        alpha: String,
+        #[dataclass(skip)]
+        scratch: u8,
+        mid: bool,
+    }
+
+    let record = Record::new(1, "a".to_string(), 0, true);
+    let keys: Vec<_> = record
+        .as_ordered_dict()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, ["zeta", "alpha", "mid"]);
+
+    let dict = record.as_dict();
+    assert_eq!(dict.len(), 3);
+    assert_eq!(dict["alpha"], Value::String("a".to_string()));
+}
+
+#[test]
+fn cow_defaults_borrow_nothing() {
+    use std::borrow::Cow;
+
+    #[derive(Dataclass)]
+    struct Label<T> {
+        value: T,
+        #[dataclass(default)]
+        name: Cow<'static, str>,
+        #[dataclass(default)]
+        bytes: Cow<'static, [u8]>,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(const_default)]
+    struct Tag {
+        #[dataclass(default)]
+        name: Cow<'static, str>,
+        #[dataclass(default)]
+        bytes: Cow<'static, [u8]>,
+    }
+
+    let label = Label::new(3);
+    assert_eq!(label.name, "");
+    assert!(label.bytes.is_empty());
+
+    const EMPTY: Tag = Tag::EMPTY;
+    assert!(matches!(EMPTY.name, Cow::Borrowed("")));
+    assert!(EMPTY.bytes.is_empty());
+}
+
+#[test]
+fn as_vec_round_trips_through_try_from_values() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(as_vec)]
+    struct Row {
+        id: u32,
+        name: String,
+        #[dataclass(skip, default)]
+        cache: Vec<u8>,
+        score: Option<f64>,
+    }
+
+    let row = Row::new(7, "seven".to_string(), Some(0.5));
+    let values = row.as_vec();
+    assert_eq!(values.len(), 3);
+    assert_eq!(Row::try_from_values(values).unwrap(), row);
+
+    assert_eq!(
+        Row::try_from_values(vec![Value::U32(1)]).unwrap_err(),
+        "expected 3 values, got 1"
+    );
+    let wrong = vec![
+        Value::String("one".to_string()),
+        Value::String("x".to_string()),
+        Value::Option(None),
+    ];
+    assert!(Row::try_from_values(wrong)
+        .unwrap_err()
+        .starts_with("field `id`: "));
+}
+
+#[test]
+fn compare_false_drops_field_and_its_bound() {
+    struct Opaque;
+
+    #[derive(Dataclass)]
+    struct Job<F> {
+        id: u32,
+        #[dataclass(compare = false)]
+        run: F,
+        #[dataclass(compare = false)]
+        handle: Opaque,
+    }
+
+    let noop = |x: u32| x;
+    let a = Job::new(1, noop, Opaque);
+    let b = Job::new(1, noop, Opaque);
+    assert!(a == b);
+    assert!(a != Job::new(2, noop, Opaque));
+}
+
+#[test]
+fn debug_pretty_prints_nested_dataclasses() {
+    #[derive(Dataclass)]
+    #[dataclass(debug)]
+    struct Point(i32, i32);
+
+    #[derive(Dataclass)]
+    #[dataclass(debug)]
+    struct Line {
+        start: Point,
+        end: Point,
+        #[dataclass(repr = false)]
+        cache: Vec<u8>,
+    }
+
+    let line = Line::new(Point(0, 1), Point(2, 3), vec![9]);
+    assert_eq!(
+        format!("{:?}", line),
+        "Line { start: Point(0, 1), end: Point(2, 3), .. }"
+    );
+    assert_eq!(
+        format!("{:#?}", line),
+        "Line {
+    start: Point(
+        0,
+        1,
+    ),
+    end: Point(
+        2,
+        3,
+    ),
+    ..
+}"
+    );
+}
+
+#[test]
+fn default_expr_sizes_arrays_by_const_params() {
+    #[derive(Dataclass)]
+    struct Matrix<const R: usize, const C: usize> {
+        #[dataclass(default = "[[0.0; C]; R]")]
+        data: [[f64; C]; R],
+    }
+
+    let matrix = Matrix::<2, 3>::default();
+    assert_eq!(matrix.data.len(), 2);
+    assert!(matrix.data.iter().all(|row| row.len() == 3));
+    assert!(Matrix::<1, 1>::new() == Matrix { data: [[0.0]] });
+}
+
+#[test]
+fn skip_serializing_if_omits_field_from_dict() {
+    #[derive(Dataclass)]
+    #[dataclass(as_dict)]
+    struct Contact {
+        name: String,
+        #[dataclass(skip_serializing_if = "Option::is_none")]
+        email: Option<String>,
+    }
+
+    let without = Contact::new("Ann".to_string(), None);
+    assert_eq!(without.as_ordered_dict().len(), 1);
+    assert!(!without.as_dict().contains_key("email"));
+
+    let with = Contact::new("Ann".to_string(), Some("ann@example.com".to_string()));
+    assert!(with.as_dict().contains_key("email"));
+}
+
+#[test]
+fn new_boxed_matches_new() {
+    trait Area {
+        fn area(&self) -> u32;
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(boxed_ctor)]
+    struct Rect {
+        width: u32,
+        #[dataclass(default = "1")]
+        height: u32,
+    }
+
+    impl Area for Rect {
+        fn area(&self) -> u32 {
+            self.width * self.height
+        }
+    }
+
+    let rect = Rect::new_boxed(4);
+    assert!(*rect == Rect::new(4));
+    let shapes: Vec<Box<dyn Area>> = vec![rect, Rect::new_boxed(2)];
+    assert_eq!(shapes.iter().map(|shape| shape.area()).sum::<u32>(), 6);
+}
+
+#[test]
+fn repr_limit_truncates_debug_output() {
+    #[derive(Dataclass)]
+    #[dataclass(debug)]
+    struct Samples<T> {
+        name: &'static str,
+        #[dataclass(repr_limit = 5)]
+        values: Vec<T>,
+        #[dataclass(repr_limit = 5)]
+        short: Vec<u8>,
+    }
+
+    let samples = Samples::new("ramp", (0..100).collect::<Vec<u32>>(), vec![1, 2]);
+    assert_eq!(
+        format!("{:?}", samples),
+        "Samples { name: \"ramp\", values: [0, 1, 2, 3, 4, ...], short: [1, 2] }"
+    );
+}
+
+#[test]
+fn associated_type_option_defaults_to_none() {
+    struct Opaque;
+
+    #[derive(Dataclass)]
+    struct Peekable<I: Iterator> {
+        iter: I,
+        #[dataclass(default)]
+        peeked: Option<I::Item>,
+    }
+
+    let mut peekable = Peekable::new(vec![Opaque, Opaque].into_iter());
+    assert!(peekable.peeked.is_none());
+    peekable.peeked = peekable.iter.next();
+    assert!(peekable.peeked.is_some());
+}
+
+#[test]
+fn reset_restores_defaulted_fields() {
+    fn fresh_buffer() -> Vec<u8> {
+        Vec::with_capacity(16)
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(reset)]
+    struct Connection {
+        id: u32,
+        #[dataclass(default_factory = "fresh_buffer")]
+        buffer: Vec<u8>,
+        #[dataclass(default = "3")]
+        retries: u8,
+        #[dataclass(default)]
+        error: Option<String>,
+    }
+
+    let mut connection = Connection::new(9);
+    connection.buffer.extend_from_slice(b"abc");
+    connection.retries = 0;
+    connection.error = Some("timeout".to_string());
+
+    connection.reset();
+    assert!(connection == Connection::new(9));
+    assert_eq!(connection.id, 9);
+}
+
+#[test]
+fn default_exprs_with_macros_commas_and_turbofish() {
+    #[derive(Dataclass)]
+    struct Defaults {
+        #[dataclass(default = "format!(\"{}-{}\", 1, 2)")]
+        label: String,
+        #[dataclass(default = "vec![0; 10]")]
+        zeros: Vec<u8>,
+        #[dataclass(default = "vec![1, 2]")]
+        pair: Vec<u8>,
+        #[dataclass(default = "[1, 2, 3].iter().copied().collect::<Vec<_>>()")]
+        collected: Vec<i32>,
+    }
+
+    let defaults = Defaults::new();
+    assert_eq!(defaults.label, "1-2");
+    assert_eq!(defaults.zeros, [0; 10]);
+    assert_eq!(defaults.pair, [1, 2]);
+    assert_eq!(defaults.collected, [1, 2, 3]);
+}
+
+#[test]
+fn getter_prefix_namespaces_accessors() {
+    #[derive(Dataclass)]
+    #[dataclass(accessors, getter_prefix = "get_")]
+    struct User {
+        name: String,
+    }
+
+    let mut user = User::new("ada".to_string());
+    user.get_name_mut().push_str(" lovelace");
+    assert_eq!(user.get_name(), "ada lovelace");
+}
+
+#[test]
+fn field_count_sizes_arrays() {
+    #[derive(Dataclass)]
+    struct Rgb(u8, u8, u8);
+
+    #[derive(Dataclass)]
+    struct Empty;
+
+    let channels: [&str; Rgb::FIELD_COUNT] = ["red", "green", "blue"];
+    assert_eq!(channels.len(), 3);
+    const NONE: [u8; Empty::FIELD_COUNT] = [];
+    assert!(NONE.is_empty());
+}
+
+#[test]
+fn default_all_optional_leaves_options_out_of_new() {
+    #[derive(Dataclass)]
+    #[dataclass(default_all_optional)]
+    struct Config {
+        host: String,
+        port: Option<u16>,
+        #[dataclass(default = "Some(30)")]
+        timeout: Option<u32>,
+        user: Option<String>,
+        retries: u8,
+    }
+
+    let config = Config::new("localhost".to_string(), 2);
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, None);
+    assert_eq!(config.timeout, Some(30));
+    assert_eq!(config.user, None);
+    assert_eq!(config.retries, 2);
+}
+
+#[test]
+fn boxed_closure_option_defaults_to_none() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Dataclass)]
+    struct Button {
+        label: String,
+        #[dataclass(default, compare = false)]
+        on_click: Option<Box<dyn Fn()>>,
+    }
+
+    let mut button = Button::new("OK".to_string());
+    assert!(button.on_click.is_none());
+    assert!(button == Button::new("OK".to_string()));
+
+    let clicks = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&clicks);
+    button.on_click = Some(Box::new(move || counter.set(counter.get() + 1)));
+    if let Some(on_click) = &button.on_click {
+        on_click();
+    }
+    assert_eq!(clicks.get(), 1);
+}
+
+#[test]
+fn eq_order_compares_listed_fields_first() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static COMPARISONS: AtomicU32 = AtomicU32::new(0);
+
+    struct Counted(String);
+
+    impl PartialEq for Counted {
+        fn eq(&self, other: &Self) -> bool {
+            COMPARISONS.fetch_add(1, Ordering::Relaxed);
+            self.0 == other.0
+        }
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(eq_order = "age")]
+    struct Person {
+        name: Counted,
+        bio: String,
+        age: u32,
+    }
+
+    let person = |age| Person::new(Counted("Ann".to_string()), String::new(), age);
+    assert!(person(30) == person(30));
+    assert_eq!(COMPARISONS.load(Ordering::Relaxed), 1);
+    assert!(person(30) != person(31));
+    assert_eq!(COMPARISONS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn into_fields_accept_conversions_in_new() {
+    #[derive(Dataclass)]
+    #[dataclass(option_ctor)]
+    struct Pet {
+        #[dataclass(into)]
+        name: String,
+        age: u8,
+    }
+
+    let pet = Pet::new("Rex", 3);
+    assert_eq!(pet.name, "Rex");
+    assert_eq!(pet.age, 3);
+    assert!(Pet::checked_new(String::from("Tom"), 1).is_some());
+}
+
+#[test]
+fn from_defaults_and_applies_builder_overrides() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(builder)]
+    struct Profile {
+        name: String,
+        #[dataclass(default = "18")]
+        age: u8,
+        #[dataclass(default)]
+        admin: bool,
+    }
+
+    let profile = Profile::from_defaults_and(|b| {
+        b.name("Alice".into());
+        b.age(30);
+    })
+    .unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            name: "Alice".to_string(),
+            age: 30,
+            admin: false,
+        }
+    );
+    assert!(Profile::from_defaults_and(|b| {
+        b.admin(true);
+    })
+    .is_err());
+}
+
+#[test]
+fn default_expr_can_name_self() {
+    #[derive(Dataclass)]
+    #[dataclass(builder)]
+    struct Member {
+        name: String,
+        #[dataclass(default = "Self::DEFAULT_AGE")]
+        age: u8,
+    }
+
+    impl Member {
+        const DEFAULT_AGE: u8 = 21;
+    }
+
+    assert_eq!(Member::new("Bo".to_string()).age, 21);
+    let built = Member::builder().name("Al".to_string()).build().unwrap();
+    assert_eq!(built.age, 21);
+}
+
+#[test]
+fn compare_by_uses_projected_key() {
+    #[derive(Dataclass)]
+    #[dataclass(compare_by = "identity")]
+    struct Account {
+        id: u64,
+        region: &'static str,
+        display_name: String,
+    }
+
+    impl Account {
+        fn identity(&self) -> (u64, &str) {
+            (self.id, self.region)
+        }
+    }
+
+    let a = Account::new(1, "eu", "Ann".to_string());
+    assert!(a == Account::new(1, "eu", "Annie".to_string()));
+    assert!(a != Account::new(1, "us", "Ann".to_string()));
+}
+
+#[test]
+fn derives_on_macro_generated_structs() {
+    macro_rules! record {
+        ($name:ident { $($field:ident: $ty:ty = $default:literal),* $(,)? }) => {
+            #[derive(Dataclass)]
+            #[dataclass(iter_fields)]
+            struct $name {
+                $(
+                    #[dataclass(default = $default)]
+                    $field: $ty,
+                )*
+            }
+        };
+    }
+
+    record!(Settings {
+        width: u32 = "640",
+        height: u32 = "480",
+        title: String = "String::from(\"untitled\")",
+    });
+
+    let settings = Settings::default();
+    assert_eq!((settings.width, settings.height), (640, 480));
+    assert_eq!(settings.title, "untitled");
+    let keys: Vec<_> = settings.iter_fields().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["width", "height", "title"]);
+}
+
+#[test]
+fn option_helpers_set_and_clear() {
+    #[derive(Dataclass)]
+    #[dataclass(option_helpers)]
+    struct Request {
+        url: String,
+        #[dataclass(default)]
+        timeout: Option<u32>,
+        #[dataclass(default)]
+        body: Option<String>,
+    }
+
+    let mut request = Request::new("/".to_string());
+    request.set_timeout(30).set_body("{}".to_string());
+    assert_eq!(request.timeout, Some(30));
+    assert_eq!(request.body.as_deref(), Some("{}"));
+
+    request.clear_timeout();
+    assert_eq!(request.timeout, None);
+    assert!(request.body.is_some());
+}
+
+#[test]
+fn from_str_parses_through_the_inner_type() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(from_str)]
+    struct Port(u16);
+
+    assert_eq!("8080".parse::<Port>().unwrap().0, 8080);
+    assert!("http".parse::<Port>().is_err());
+}
+
+#[test]
+fn hash_by_hashes_only_the_named_fields() {
+    use std::collections::HashSet;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(hash_by(id))]
+    struct Session {
+        id: u64,
+        #[dataclass(compare = false)]
+        last_seen: u64,
+    }
+
+    // Equal on `id`, so `Eq`/`Hash` agree and the set keeps one entry.
+    impl Eq for Session {}
+
+    let mut sessions = HashSet::new();
+    sessions.insert(Session::new(1, 100));
+    sessions.insert(Session::new(1, 200));
+    sessions.insert(Session::new(2, 100));
+    assert_eq!(sessions.len(), 2);
+}
+
+#[test]
+fn derive_all_derives_each_item() {
+    dataclasses_derive::derive_all! {
+        #[dataclass(debug)]
+        struct Author {
+            name: String,
+        }
+
+        struct Book {
+            title: String,
+            #[dataclass(default = "1")]
+            edition: u32,
+        }
+    }
+
+    let author = Author::new("Le Guin".to_string());
+    assert_eq!(format!("{:?}", author), "Author { name: \"Le Guin\" }");
+    let book = Book::new("The Dispossessed".to_string());
+    assert_eq!(book.edition, 1);
+    assert!(book == Book::new("The Dispossessed".to_string()));
+}
+
+#[test]
+fn default_expr_relies_only_on_declared_bounds() {
+    #[derive(Dataclass)]
+    struct Seeded<T: Default + Clone> {
+        #[dataclass(default = "T::default().clone()")]
+        seed: T,
+        #[dataclass(default)]
+        spare: T,
+        count: usize,
+    }
+
+    let seeded = Seeded::<String>::new(2);
+    assert_eq!(seeded.seed, "");
+    assert_eq!(seeded.spare, "");
+    assert_eq!(seeded.count, 2);
+}
+
+#[test]
+fn order_and_bounded_span_the_value_range() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(order, bounded)]
+    struct Point {
+        x: i32,
+        y: f64,
+    }
+
+    assert!(Point::max_value() > Point::min_value());
+    assert_eq!(Point::min_value().x, i32::MIN);
+    assert!(Point::new(1, 5.0) < Point::new(2, 0.0));
+    assert!(Point::new(1, 5.0) > Point::new(1, 4.5));
+}
+
+#[test]
+fn flatten_merges_nested_dict_entries() {
+    #[derive(Dataclass)]
+    #[dataclass(as_dict)]
+    struct Address {
+        city: String,
+        zip: String,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(as_dict)]
+    struct Customer {
+        name: String,
+        #[dataclass(flatten)]
+        address: Address,
+    }
+
+    let customer = Customer::new(
+        "Ann".to_string(),
+        Address::new("Oslo".to_string(), "0150".to_string()),
+    );
+    let keys: Vec<_> = customer
+        .as_ordered_dict()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    assert_eq!(keys, ["name", "city", "zip"]);
+    assert_eq!(
+        customer.as_dict()["city"],
+        Value::String("Oslo".to_string())
+    );
+}
+
+#[test]
+fn derives_alongside_repr_attributes() {
+    #[derive(Dataclass, Debug)]
+    #[repr(C)]
+    struct Vertex {
+        #[doc = "Position."]
+        position: [f32; 3],
+        #[dataclass(default)]
+        #[allow(unused)]
+        weight: f32,
+    }
+
+    #[derive(Dataclass)]
+    #[repr(transparent)]
+    struct Meters(f64);
+
+    let vertex = Vertex::new([0.0, 1.0, 2.0]);
+    assert_eq!(vertex.weight, 0.0);
+    assert_eq!(std::mem::size_of::<Vertex>(), 16);
+    assert!(Meters::new(1.5) == Meters(1.5));
+}
+
+#[test]
+fn from_field_converts_the_required_value() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(from_field)]
+    struct Timeout {
+        #[dataclass(default = "3")]
+        retries: u8,
+        seconds: u32,
+        #[dataclass(default)]
+        label: Option<String>,
+    }
+
+    let timeout: Timeout = 30.into();
+    assert_eq!(timeout, Timeout::new(30));
+    assert_eq!(timeout.retries, 3);
+}
+
+#[test]
+fn boxed_slice_defaults_are_empty() {
+    struct Opaque;
+
+    #[derive(Dataclass)]
+    struct Buffers<T> {
+        #[dataclass(default)]
+        bytes: Box<[u8]>,
+        #[dataclass(default)]
+        names: Box<[String]>,
+        #[dataclass(default, compare = false)]
+        items: Box<[T]>,
+    }
+
+    let buffers = Buffers::<Opaque>::new();
+    assert!(buffers.bytes.is_empty());
+    assert!(buffers.names.is_empty());
+    assert!(buffers.items.is_empty());
+}
+
+#[test]
+fn equivalent_looks_up_wrapper_keys_by_inner_value() {
+    use std::collections::HashMap;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(hash, equivalent)]
+    struct Sku(u64);
+
+    impl Eq for Sku {}
+
+    let mut stock = HashMap::new();
+    stock.insert(Sku(1001), 4);
+    stock.insert(Sku(1002), 0);
+    assert_eq!(stock.get(&1001), Some(&4));
+    assert!(stock.contains_key(&1002));
+    assert!(!stock.contains_key(&9));
+}
+
+#[test]
+fn default_derive_fills_required_fields_from_their_types() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(default_derive)]
+    struct Window {
+        title: String,
+        #[dataclass(default = "800")]
+        width: u32,
+        visible: bool,
+    }
+
+    let window = Window::new("Editor".to_string(), true);
+    assert_eq!(window.width, 800);
+    assert_eq!(
+        Window::default(),
+        Window {
+            title: String::new(),
+            width: 800,
+            visible: false,
+        }
+    );
+}
+
+#[test]
+#[deny(clippy::too_many_arguments)]
+fn wide_constructors_allow_many_arguments() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(boxed_ctor, option_ctor)]
+    struct Wide {
+        a: u8,
+        b: u8,
+        c: u8,
+        d: u8,
+        e: u8,
+        f: u8,
+        g: u8,
+        h: u8,
+    }
+
+    let wide = Wide::new(1, 2, 3, 4, 5, 6, 7, 8);
+    assert_eq!(wide.h, 8);
+    assert_eq!(*Wide::new_boxed(1, 2, 3, 4, 5, 6, 7, 8), wide);
+    assert_eq!(Wide::checked_new(1, 2, 3, 4, 5, 6, 7, 8), Some(wide));
+}
+
+#[test]
+fn const_block_defaults_are_evaluated_at_compile_time() {
+    // Inline `const` blocks need Rust 1.79.
+    const fn area(width: u32, height: u32) -> u32 {
+        width * height
+    }
+
+    #[derive(Dataclass, Debug)]
+    struct Tile {
+        #[dataclass(default = "const { 1 + 2 }")]
+        layer: u32,
+        #[dataclass(default = "const { area(16, 16) }")]
+        pixels: u32,
+    }
+
+    let tile = Tile::new();
+    assert_eq!(tile.layer, 3);
+    assert_eq!(tile.pixels, 256);
+}
+
+#[test]
+fn freeze_exposes_fields_only_through_getters() {
+    mod geometry {
+        use dataclasses_derive::Dataclass;
+
+        #[derive(Dataclass, Debug)]
+        #[dataclass(freeze)]
+        pub struct Point {
+            x: i32,
+            #[dataclass(default = "0")]
+            y: i32,
+        }
+    }
+
+    let point = geometry::Point::new(3);
+    assert_eq!((*point.x(), *point.y()), (3, 0));
+}
+
+#[test]
+fn defaulted_type_parameters_derive() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(builder, hash, order)]
+    struct Sample<T = i32> {
+        x: T,
+        #[dataclass(default)]
+        weight: T,
+    }
+
+    let sample: Sample = Sample::new(5);
+    assert_eq!(sample, Sample { x: 5, weight: 0 });
+    assert!(Sample::new(1) < sample);
+    let built: Sample = Sample::builder().x(7).build().unwrap();
+    assert_eq!(built.x, 7);
+}
+
+#[test]
+fn dynamic_visitor_mutates_fields_by_name() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(dynamic)]
+    struct Settings {
+        theme: String,
+        font_size: u32,
+    }
+
+    let mut settings = Settings::new("dark".to_string(), 12);
+    settings.for_each_field_mut(|name, value| {
+        if name == "font_size" {
+            if let Some(size) = value.downcast_mut::<u32>() {
+                *size *= 2;
+            }
+        }
+    });
+    assert_eq!(settings.font_size, 24);
+
+    let mut names = Vec::new();
+    settings.for_each_field(|name, value| {
+        names.push(name);
+        if name == "theme" {
+            assert_eq!(value.downcast_ref::<String>().unwrap(), "dark");
+        }
+    });
+    assert_eq!(names, ["theme", "font_size"]);
+}
+
+#[test]
+fn raw_string_defaults_embed_quotes() {
+    use std::collections::HashMap;
+
+    #[derive(Dataclass, Debug)]
+    struct Registry {
+        #[dataclass(default = r#"HashMap::from([("a", 1), ("b, c", 2)])"#)]
+        counts: HashMap<&'static str, i32>,
+        #[dataclass(default = r#""say \"hi\"".to_string()"#)]
+        greeting: String,
+    }
+
+    let registry = Registry::new();
+    assert_eq!(registry.counts["a"], 1);
+    assert_eq!(registry.counts["b, c"], 2);
+    assert_eq!(registry.greeting, "say \"hi\"");
+}
+
+#[test]
+fn full_enables_the_batteries_included_impls() {
+    use std::collections::HashSet;
+
+    #[derive(Dataclass, Debug, Clone, Eq, serde::Serialize)]
+    #[dataclass(full)]
+    struct Version {
+        major: u32,
+        minor: u32,
+    }
+
+    let old = Version::new(1, 4);
+    let new = old.clone().with_major(2).with_minor(0);
+    assert!(old < new);
+    assert_eq!(new.to_string(), "Version(major=2, minor=0)");
+    let seen: HashSet<_> = vec![old.clone(), new, old].into_iter().collect();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(
+        Version::new(3, 1).as_dict()["minor"],
+        serde_value::Value::U32(1)
+    );
+
+    #[derive(Dataclass)]
+    #[dataclass(display)]
+    struct Pair(&'static str, #[dataclass(repr = false)] u8, i32);
+
+    assert_eq!(Pair::new("x", 0, -1).to_string(), "Pair(x, -1)");
+}
+
+#[test]
+fn namespace_qualifies_the_schema_name() {
+    #[derive(Dataclass)]
+    #[dataclass(schema, namespace = "myapp.models")]
+    struct Account {
+        id: u64,
+    }
+
+    assert_eq!(Account::NAMESPACE, "myapp.models");
+    assert_eq!(Account::schema()["name"], "myapp.models.Account");
+}
+
+#[test]
+fn diff_dict_reports_changed_fields_with_old_and_new_values() {
+    use serde_value::Value;
+
+    #[derive(Dataclass, Clone)]
+    #[dataclass(as_dict)]
+    struct Profile {
+        name: String,
+        age: u8,
+        city: String,
+        #[dataclass(compare = false)]
+        updated_at: u64,
+        #[dataclass(skip, default)]
+        cache: Option<u8>,
+    }
+
+    let before = Profile::new("Ada".to_string(), 36, "London".to_string(), 1);
+    let mut after = before.clone();
+    after.age = 37;
+    after.city = "Paris".to_string();
+    after.updated_at = 2;
+    after.cache = Some(1);
+
+    let diff = before.diff_dict(&after);
+    assert_eq!(diff.len(), 2);
+    assert_eq!(diff["age"], (Value::U8(36), Value::U8(37)));
+    assert_eq!(
+        diff["city"],
+        (
+            Value::String("London".to_string()),
+            Value::String("Paris".to_string())
+        )
+    );
+    assert!(before.diff_dict(&before).is_empty());
+}
+
+#[test]
+fn non_zero_fields_take_an_explicit_default() {
+    use std::num::NonZeroU32;
+
+    #[derive(Dataclass, Debug)]
+    struct Pool {
+        #[dataclass(default = "NonZeroU32::new(1).unwrap()")]
+        workers: NonZeroU32,
+    }
+
+    assert_eq!(Pool::new().workers.get(), 1);
+
+    // Only the standard `NonZero` types are turned away.
+    #[derive(Debug, Default, PartialEq)]
+    struct NonZeroBuffer(Vec<u8>);
+
+    #[derive(Dataclass, Debug)]
+    struct Stream {
+        #[dataclass(default)]
+        buffer: NonZeroBuffer,
+    }
+
+    assert_eq!(Stream::new().buffer, NonZeroBuffer::default());
+}
+
+#[test]
+fn private_new_leaves_only_the_builder_public() {
+    mod accounts {
+        use dataclasses_derive::Dataclass;
+
+        fn check_email(email: &String) -> Result<(), String> {
+            if email.contains('@') {
+                Ok(())
+            } else {
+                Err(format!("invalid email `{}`", email))
+            }
+        }
+
+        #[derive(Dataclass, Debug)]
+        #[dataclass(builder, private_new)]
+        pub struct Account {
+            #[dataclass(validate = "check_email")]
+            email: String,
+        }
+
+        impl Account {
+            pub fn email(&self) -> &str {
+                &self.email
+            }
+        }
+    }
+
+    use accounts::Account;
+
+    let account = Account::builder()
+        .email("ada@example.com".to_string())
+        .build()
+        .unwrap();
+    assert_eq!(account.email(), "ada@example.com");
+    assert_eq!(
+        Account::builder()
+            .email("ada".to_string())
+            .build()
+            .unwrap_err(),
+        "invalid email `ada`"
+    );
+}
+
+#[test]
+fn wide_structs_derive_without_hitting_limits() {
+    macro_rules! wide {
+        ($($field:ident)*) => {
+            #[derive(Dataclass, Clone)]
+            #[dataclass(hash, order, debug, as_dict, iter_fields)]
+            struct Wide {
+                $(#[dataclass(default)] $field: u32,)*
+            }
+        };
+    }
+
+    wide! {
+        f0 f1 f2 f3 f4 f5 f6 f7 f8 f9 f10 f11 f12 f13 f14 f15 f16 f17 f18 f19 f20 f21 f22 f23
+        f24 f25 f26 f27 f28 f29 f30 f31 f32 f33 f34 f35 f36 f37 f38 f39 f40 f41 f42 f43 f44 f45
+        f46 f47 f48 f49 f50 f51 f52 f53 f54 f55 f56 f57 f58 f59 f60 f61 f62 f63 f64 f65 f66 f67
+        f68 f69 f70 f71 f72 f73 f74 f75 f76 f77 f78 f79 f80 f81 f82 f83 f84 f85 f86 f87 f88 f89
+        f90 f91 f92 f93 f94 f95 f96 f97 f98 f99 f100 f101 f102 f103 f104 f105 f106 f107 f108
+        f109 f110 f111 f112 f113 f114 f115 f116 f117 f118 f119 f120 f121 f122 f123 f124 f125
+        f126 f127 f128 f129 f130 f131 f132 f133 f134 f135 f136 f137 f138 f139 f140 f141 f142
+        f143 f144 f145 f146 f147 f148 f149 f150 f151 f152 f153 f154 f155 f156 f157 f158 f159
+        f160 f161 f162 f163 f164 f165 f166 f167 f168 f169 f170 f171 f172 f173 f174 f175 f176
+        f177 f178 f179 f180 f181 f182 f183 f184 f185 f186 f187 f188 f189 f190 f191 f192 f193
+        f194 f195 f196 f197 f198 f199
+    }
+
+    let first = Wide::new();
+    let mut second = first.clone();
+    assert_eq!(first, second);
+    second.f199 = 1;
+    assert_ne!(first, second);
+    assert!(first < second);
+    assert_eq!(Wide::FIELD_COUNT, 200);
+    assert_eq!(second.iter_fields().count(), 200);
+}
+
+#[test]
+fn name_seeded_hash_tells_swapped_fields_apart() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(hash(name_seeded))]
+    struct Seeded {
+        width: u32,
+        height: u32,
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(hash(name_seeded))]
+    struct Swapped {
+        height: u32,
+        width: u32,
+    }
+
+    // Same values in the same positions, but under different field names.
+    assert_ne!(hash_of(&Seeded::new(3, 4)), hash_of(&Swapped::new(3, 4)));
+    assert_ne!(hash_of(&Seeded::new(3, 4)), hash_of(&Seeded::new(4, 3)));
+    assert_eq!(hash_of(&Seeded::new(3, 4)), hash_of(&Seeded::new(3, 4)));
+}
+
+#[test]
+fn extends_projects_down_to_the_base_fields() {
+    #[derive(Dataclass, Debug)]
+    struct Entity {
+        id: u64,
+        #[dataclass(default)]
+        version: u32,
+    }
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(extends = "Entity")]
+    struct Invoice {
+        #[dataclass(base)]
+        id: u64,
+        total: u32,
+        #[dataclass(base, default = "3")]
+        version: u32,
+    }
+
+    let entity = Entity::from(Invoice::new(7, 120));
+    assert_eq!(entity, Entity { id: 7, version: 3 });
+}
+
+#[test]
+fn defaults_are_evaluated_per_value_not_shared() {
+    #[derive(Dataclass, Debug)]
+    struct Basket {
+        #[dataclass(default = "vec![\"apple\".to_string()]")]
+        items: Vec<String>,
+    }
+
+    let mut first = Basket::new();
+    let second = Basket::new();
+    first.items.push("pear".to_string());
+    assert_eq!(first.items, ["apple", "pear"]);
+    assert_eq!(second.items, ["apple"]);
+    assert_eq!(Basket::default().items, ["apple"]);
+}
+
+#[test]
+fn schema_hash_tracks_the_field_shape() {
+    mod v1 {
+        use dataclasses_derive::Dataclass;
+
+        #[derive(Dataclass)]
+        #[dataclass(schema)]
+        pub struct Record {
+            id: u64,
+            name: String,
+        }
+    }
+
+    mod v2 {
+        use dataclasses_derive::Dataclass;
+
+        #[derive(Dataclass)]
+        #[dataclass(schema)]
+        pub struct Record {
+            id: u64,
+            name: String,
+            email: String,
+        }
+    }
+
+    mod renamed {
+        use dataclasses_derive::Dataclass;
+
+        #[derive(Dataclass)]
+        #[dataclass(schema)]
+        pub struct Entry {
+            id: u64,
+            name: String,
+        }
+    }
+
+    const HASH: u64 = v1::Record::schema_hash();
+    assert_eq!(HASH, v1::Record::schema_hash());
+    assert_eq!(HASH, renamed::Entry::schema_hash());
+    assert_ne!(HASH, v2::Record::schema_hash());
+
+    // Types are hashed in a fixed spelling, not however the compiler prints
+    // their tokens, so the value is pinned across toolchains.
+    const fn fnv1a(bytes: &[u8]) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash = (hash ^ bytes[index] as u64).wrapping_mul(0x0100_0000_01b3);
+            index += 1;
+        }
+        hash
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(schema)]
+    struct Spelled {
+        items: Vec<Option<u8>>,
+        label: &'static str,
+        pair: [u8; 2],
+    }
+
+    assert_eq!(
+        Spelled::schema_hash(),
+        fnv1a(b"items\0Vec<Option<u8>>\0label\0&'static str\0pair\0[u8;2]\0")
+    );
+}
+
+#[test]
+fn generic_collection_defaults_infer_or_take_turbofish() {
+    use std::collections::HashMap;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(builder)]
+    struct Scores {
+        #[dataclass(default)]
+        inferred: HashMap<String, i32>,
+        #[dataclass(default = "HashMap::new()")]
+        bare: HashMap<String, i32>,
+        #[dataclass(default = "HashMap::<String, i32>::new()")]
+        explicit: HashMap<String, i32>,
+    }
+
+    let mut scores = Scores::new();
+    scores.inferred.insert("a".to_string(), 1);
+    scores.explicit.insert("b".to_string(), 2);
+    assert!(scores.bare.is_empty());
+    assert_eq!(scores.explicit["b"], 2);
+    assert_eq!(Scores::builder().build().unwrap(), Scores::default());
+}
+
+#[test]
+fn try_into_tuple_runs_validators() {
+    use std::convert::TryInto;
+
+    fn check_port(port: &u16) -> Result<(), String> {
+        if *port == 0 {
+            Err("port must be nonzero".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(try_into_tuple)]
+    struct Endpoint {
+        host: String,
+        #[dataclass(validate = "check_port")]
+        port: u16,
+    }
+
+    let tuple: Result<(String, u16), String> =
+        Endpoint::new("localhost".to_string(), 80).try_into();
+    assert_eq!(tuple.unwrap(), ("localhost".to_string(), 80));
+    let invalid: Result<(String, u16), String> =
+        Endpoint::new("localhost".to_string(), 0).try_into();
+    assert_eq!(invalid.unwrap_err(), "port must be nonzero");
+}
+
+#[test]
+fn derives_inside_impl_blocks_and_trait_default_methods() {
+    trait Shape {
+        fn corner(&self) -> (i32, i32) {
+            #[derive(Dataclass, Debug)]
+            #[dataclass(builder, hash)]
+            struct Corner {
+                x: i32,
+                #[dataclass(default = "-1")]
+                y: i32,
+            }
+
+            let corner = Corner::builder().x(2).build().unwrap();
+            assert_eq!(corner, Corner::new(2));
+            (corner.x, corner.y)
+        }
+    }
+
+    struct Square;
+
+    impl Shape for Square {}
+
+    impl Square {
+        fn origin() -> (i32, i32) {
+            #[derive(Dataclass, Debug)]
+            #[dataclass(order)]
+            struct Origin {
+                #[dataclass(default)]
+                x: i32,
+                #[dataclass(default)]
+                y: i32,
+            }
+
+            let origin = Origin::default();
+            assert!(origin < Origin { x: 0, y: 1 });
+            (origin.x, origin.y)
+        }
+    }
+
+    assert_eq!(Square.corner(), (2, -1));
+    assert_eq!(Square::origin(), (0, 0));
+}
+
+#[test]
+fn as_kwargs_keeps_declaration_order_and_renames() {
+    use serde_value::Value;
+
+    #[derive(Dataclass)]
+    #[dataclass(as_dict, rename_all = "camelCase")]
+    struct Person {
+        last_name: String,
+        first_name: String,
+        #[dataclass(repr = false)]
+        password: String,
+        age: u8,
+    }
+
+    let person = Person::new(
+        "Lovelace".to_string(),
+        "Ada".to_string(),
+        "hunter2".to_string(),
+        36,
+    );
+    assert_eq!(
+        person.as_kwargs(),
+        vec![
+            ("lastName", Value::String("Lovelace".to_string())),
+            ("firstName", Value::String("Ada".to_string())),
+            ("age", Value::U8(36)),
+        ]
+    );
+}
+
+#[test]
+fn block_defaults_with_control_flow() {
+    #[derive(Dataclass, Debug)]
+    struct Tuning {
+        #[dataclass(default = "{ if cfg!(test) { 1 } else { 2 } }")]
+        retries: u32,
+        #[dataclass(
+            default = "{ let mode = Some(3); if let Some(n) = mode { n * 2 } else { 0 } }"
+        )]
+        threads: u32,
+        #[dataclass(default = "matches!(Some(4), Some(n) if n % 2 == 0)")]
+        even: bool,
+    }
+
+    let tuning = Tuning::new();
+    assert_eq!(tuning.retries, 1);
+    assert_eq!(tuning.threads, 6);
+    assert!(tuning.even);
+}
+
+#[test]
+fn total_float_eq_treats_matching_nans_as_equal() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(total_float_eq)]
+    struct Sample {
+        label: String,
+        reading: f64,
+        #[dataclass(default)]
+        weight: f32,
+    }
+
+    let missing = Sample::new("probe".to_string(), f64::NAN);
+    assert_eq!(missing, Sample::new("probe".to_string(), f64::NAN));
+    assert_ne!(missing, Sample::new("probe".to_string(), 1.0));
+
+    fn assert_total<T: Eq>(_: &T) {}
+    assert_total(&missing);
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(total_float_eq)]
+    struct Tagged<T> {
+        tag: T,
+        value: f32,
+    }
+
+    assert_eq!(Tagged::new(1, f32::NAN), Tagged::new(1, f32::NAN));
+    assert_total(&Tagged::new(1, 0.5));
+}
+
+#[test]
+fn float_fields_hash_by_their_bits() {
+    use std::collections::HashMap;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(hash, total_float_eq)]
+    struct Coordinate {
+        lat: f64,
+        lon: f64,
+        label: &'static str,
+    }
+
+    let mut names = HashMap::new();
+    names.insert(Coordinate::new(51.5, -0.1, "city"), "London");
+    names.insert(Coordinate::new(f64::NAN, 0.0, "unknown"), "Nowhere");
+    assert_eq!(names[&Coordinate::new(51.5, -0.1, "city")], "London");
+    assert_eq!(names[&Coordinate::new(f64::NAN, 0.0, "unknown")], "Nowhere");
+    assert!(!names.contains_key(&Coordinate::new(51.5, -0.2, "city")));
+}
+
+#[test]
+fn signed_zeros_hash_alike_when_they_compare_equal() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(hash)]
+    struct Offset {
+        x: f64,
+    }
+
+    assert_eq!(Offset::new(0.0), Offset::new(-0.0));
+    assert_eq!(hash_of(&Offset::new(0.0)), hash_of(&Offset::new(-0.0)));
+    assert_ne!(hash_of(&Offset::new(0.0)), hash_of(&Offset::new(1.0)));
+
+    // Under `total_float_eq` the zeros differ, and so may their hashes.
+    #[derive(Dataclass, Debug)]
+    #[dataclass(hash, total_float_eq)]
+    struct Exact {
+        x: f64,
+    }
+
+    assert_ne!(Exact::new(0.0), Exact::new(-0.0));
+}
+
+#[test]
+fn result_fields_take_an_explicit_default() {
+    #[derive(Dataclass, Debug)]
+    struct Job {
+        #[dataclass(default = "Ok(0)")]
+        status: Result<i32, String>,
+        #[dataclass(default = "Err(\"not started\".to_string())")]
+        outcome: Result<(), String>,
+    }
+
+    let job = Job::new();
+    assert_eq!(job.status, Ok(0));
+    assert_eq!(job.outcome.unwrap_err(), "not started");
+}
+
+#[test]
+fn builder_setter_prefix_renames_setters() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(builder, builder_setter_prefix = "set_")]
+    struct Request {
+        url: String,
+        #[dataclass(default = "30")]
+        timeout: u32,
+    }
+
+    let request = Request::builder()
+        .set_url("https://example.com".to_string())
+        .set_timeout(5)
+        .build()
+        .unwrap();
+    assert_eq!(
+        request,
+        Request {
+            url: "https://example.com".to_string(),
+            timeout: 5
+        }
+    );
+}
+
+#[test]
+fn debug_with_formats_a_field_through_a_function() {
+    use std::fmt;
+
+    fn hex(value: &u32, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010x}", value)
+    }
+
+    struct Handle;
+
+    fn opaque(_: &Handle, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<handle>")
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(debug, eq = false)]
+    struct Frame {
+        id: u8,
+        #[dataclass(debug_with = "hex")]
+        checksum: u32,
+        #[dataclass(debug_with = "opaque")]
+        handle: Handle,
+    }
+
+    let frame = Frame::new(1, 0xdead, Handle);
+    assert_eq!(
+        format!("{:?}", frame),
+        "Frame { id: 1, checksum: 0x0000dead, handle: <handle> }"
+    );
+}
+
+#[test]
+fn default_fn_builds_interdependent_defaults() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(default_fn = "Viewport::square")]
+    struct Viewport {
+        width: u32,
+        #[dataclass(default = "600")]
+        height: u32,
+        #[dataclass(default)]
+        area: u32,
+    }
+
+    impl Viewport {
+        fn square() -> Self {
+            let side = 512;
+            Viewport {
+                width: side,
+                height: side,
+                area: side * side,
+            }
+        }
+    }
+
+    assert_eq!(
+        Viewport::default(),
+        Viewport {
+            width: 512,
+            height: 512,
+            area: 262_144,
+        }
+    );
+    assert_eq!(Viewport::new(800).height, 600);
+}
+
+#[test]
+fn unrelated_attributes_are_ignored_in_any_order() {
+    #[must_use]
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    #[derive(Dataclass, Debug)]
+    #[dataclass(iter_fields)]
+    #[allow(non_camel_case_types)]
+    #[serde(deny_unknown_fields)]
+    #[repr(C)]
+    struct legacy_row {
+        #[serde(rename = "ID")]
+        #[dataclass(default = "1")]
+        #[doc = "The row id."]
+        id: u32,
+        #[cfg_attr(test, serde(skip_serializing_if = "String::is_empty"))]
+        name: String,
+    }
+
+    let row = legacy_row::new("a".to_string());
+    assert_eq!(row.id, 1);
+    assert_eq!(row.iter_fields().count(), 2);
+    assert_eq!(
+        serde_json::to_value(&row).unwrap(),
+        serde_json::json!({ "ID": 1, "NAME": "a" })
+    );
+}
+
+#[test]
+fn is_default_compares_against_the_default_value() {
+    #[derive(Dataclass, Debug)]
+    struct Filter {
+        #[dataclass(default)]
+        query: String,
+        #[dataclass(default = "10")]
+        limit: usize,
+    }
+
+    let mut filter = Filter::default();
+    assert!(filter.is_default());
+    filter.limit = 20;
+    assert!(!filter.is_default());
+
+    #[derive(Dataclass, Debug)]
+    struct Page<T> {
+        #[dataclass(default)]
+        items: Vec<T>,
+    }
+
+    assert!(Page::<u8>::new().is_default());
+    assert!(!Page { items: vec![1] }.is_default());
+}
+
+#[test]
+fn lazy_fields_compute_on_first_access() {
+    use std::cell::OnceCell;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::OnceLock;
+
+    static CALLS: AtomicU32 = AtomicU32::new(0);
+
+    fn load_index() -> Vec<u32> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        vec![1, 2, 3]
+    }
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(accessors)]
+    struct Catalog {
+        name: String,
+        #[dataclass(lazy = "load_index")]
+        index: OnceCell<Vec<u32>>,
+        #[dataclass(lazy = "u8::max_value")]
+        limit: OnceLock<u8>,
+    }
+
+    let catalog = Catalog::new("books".to_string());
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+    assert_eq!(catalog.index(), &[1, 2, 3]);
+    assert_eq!(catalog.index().len(), 3);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(*catalog.limit(), 255);
+    assert_eq!(catalog.name(), "books");
+
+    // The lazy getter is named like the accessors around it.
+    #[derive(Dataclass, Debug)]
+    #[dataclass(accessors, getter_prefix = "get_")]
+    struct Shelf {
+        label: String,
+        #[dataclass(lazy = "u8::max_value")]
+        capacity: OnceCell<u8>,
+    }
+
+    let shelf = Shelf::new("A".to_string());
+    assert_eq!(*shelf.get_capacity(), 255);
+    assert_eq!(shelf.get_label(), "A");
+}
+
+#[test]
+fn field_names_and_types_follow_declaration_order() {
+    #[derive(Dataclass)]
+    #[dataclass(rename_all = "camelCase")]
+    struct Person<T> {
+        full_name: String,
+        age: i32,
+        tags: Vec<T>,
+    }
+
+    assert_eq!(Person::<u8>::FIELD_NAMES, ["fullName", "age", "tags"]);
+    assert_eq!(Person::<u8>::FIELD_TYPES[..2], ["String", "i32"]);
+    assert_eq!(Person::<u8>::FIELD_TYPES[2].replace(' ', ""), "Vec<T>");
+    assert_eq!(Person::<u8>::FIELD_TYPES.len(), Person::<u8>::FIELD_COUNT);
+}
+
+#[test]
+fn order_desc_sorts_a_field_descending() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(order)]
+    struct Task {
+        #[dataclass(order_desc)]
+        priority: u8,
+        name: &'static str,
+    }
+
+    let mut tasks = [
+        Task::new(1, "sweep"),
+        Task::new(3, "deploy"),
+        Task::new(3, "build"),
+        Task::new(2, "test"),
+    ];
+    tasks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let names: Vec<_> = tasks.iter().map(|task| task.name).collect();
+    assert_eq!(names, ["build", "deploy", "test", "sweep"]);
+}
+
+#[test]
+fn const_new_builds_static_arrays() {
+    #[derive(Dataclass, Debug, Clone, Copy)]
+    #[dataclass(const_new)]
+    struct Point {
+        x: i32,
+        y: i32,
+        #[dataclass(default)]
+        z: i32,
+        #[dataclass(default = "1")]
+        w: i32,
+    }
+
+    static CORNERS: [Point; 3] = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1)];
+    const ORIGIN: Point = Point::new(0, 0);
+
+    assert_eq!(
+        CORNERS[2],
+        Point {
+            x: 1,
+            y: 1,
+            z: 0,
+            w: 1
+        }
+    );
+    assert_eq!(CORNERS[0], ORIGIN);
+}
+
+#[test]
+fn method_call_defaults_in_generic_structs() {
+    use std::collections::hash_map::RandomState;
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    #[derive(Dataclass, Debug)]
+    struct Buffer<T> {
+        #[dataclass(default = "Vec::with_capacity(16)")]
+        items: Vec<T>,
+    }
+
+    #[derive(Dataclass, Debug)]
+    struct Cache<K: Hash + Eq, V> {
+        #[dataclass(default = "HashMap::with_capacity_and_hasher(8, RandomState::new())")]
+        entries: HashMap<K, V>,
+    }
+
+    let mut buffer = Buffer::<String>::new();
+    assert!(buffer.items.capacity() >= 16);
+    buffer.items.push("a".to_string());
+    assert_eq!(buffer.items, ["a"]);
+
+    let cache: Cache<&str, u8> = Cache::new();
+    assert!(cache.entries.capacity() >= 8);
+}
+
+#[test]
+fn get_value_reads_fields_by_renamed_name() {
+    #[derive(Dataclass)]
+    #[dataclass(dynamic, rename_all = "camelCase")]
+    struct Order {
+        order_id: u32,
+        customer_name: String,
+        #[dataclass(skip, default)]
+        cached_total: Option<u64>,
+    }
+
+    let order = Order::new(7, "Ada".to_string());
+    assert_eq!(order.get_value("orderId"), Some(Value::U32(7)));
+    assert_eq!(
+        order.get_value("customerName"),
+        Some(Value::String("Ada".to_string()))
+    );
+    assert_eq!(order.get_value("order_id"), None);
+    assert_eq!(order.get_value("cachedTotal"), None);
+}
+
+#[test]
+fn dynamic_visits_fields_that_cannot_be_serialized() {
+    struct Handle;
+
+    #[derive(Dataclass)]
+    #[dataclass(dynamic, eq = false)]
+    struct Session {
+        user: String,
+        #[dataclass(default = "Handle")]
+        handle: Handle,
+    }
+
+    // `get_value` is only bounded on use, so the visitors still exist.
+    let session = Session::new("ada".to_string());
+    let mut names = Vec::new();
+    session.for_each_field(|name, value| {
+        names.push(name);
+        if name == "handle" {
+            assert!(value.is::<Handle>());
+        }
+    });
+    assert_eq!(names, ["user", "handle"]);
+}
+
+#[test]
+fn modified_returns_an_updated_clone() {
+    #[derive(Dataclass, Debug, Clone)]
+    #[dataclass(modified)]
+    struct Config {
+        name: String,
+        retries: u8,
+        verbose: bool,
+    }
+
+    let base = Config::new("prod".to_string(), 3, false);
+    let debug = base.modified(|config| {
+        config.name.push_str("-debug");
+        config.verbose = true;
+    });
+    assert_eq!(debug, Config::new("prod-debug".to_string(), 3, true));
+    assert_eq!(base, Config::new("prod".to_string(), 3, false));
+
+    #[derive(Dataclass, Clone)]
+    #[dataclass(frozen, modified, freeze_after = "measure")]
+    struct Label {
+        text: String,
+        #[dataclass(default)]
+        width: usize,
+    }
+
+    impl Label {
+        fn measure(&mut self) {
+            self.width = self.text.len();
+        }
+    }
+
+    let wider = Label::new("ok".to_string()).modified(|label| label.text.push_str("ay"));
+    assert_eq!(wider.width, 4);
+}
+
+#[test]
+fn cow_getters_borrow_strings_and_vecs() {
+    use std::borrow::Cow;
+
+    #[derive(Dataclass)]
+    #[dataclass(accessors)]
+    struct Article<T> {
+        #[dataclass(cow_getter)]
+        title: String,
+        #[dataclass(cow_getter)]
+        tags: Vec<T>,
+    }
+
+    let article = Article::new("Rust".to_string(), vec!['a', 'b']);
+    let title: Cow<'_, str> = article.title_cow();
+    assert!(matches!(title, Cow::Borrowed("Rust")));
+    assert_eq!(article.tags_cow().into_owned(), vec!['a', 'b']);
+}
+
+#[test]
+fn struct_flags_default_like_python() {
+    use std::fmt::{Debug, Display};
+    use std::hash::Hash;
+
+    // Resolves to the inherent `IMPLS` when the bound holds, and to the
+    // trait's fallback otherwise.
+    macro_rules! implements {
+        ($ty:ty: $bound:path) => {{
+            trait Fallback {
+                const IMPLS: bool = false;
+            }
+            impl<T: ?Sized> Fallback for T {}
+            struct Probe<T: ?Sized>(std::marker::PhantomData<T>);
+            impl<T: ?Sized + $bound> Probe<T> {
+                const IMPLS: bool = true;
+            }
+            Probe::<$ty>::IMPLS
+        }};
+    }
+
+    #[derive(Dataclass)]
+    struct Plain {
+        x: i32,
+    }
+
+    // `init`: a positional `new`; `eq`: `PartialEq`; `frozen = false`: public
+    // fields stay assignable.
+    let mut plain = Plain::new(1);
+    plain.x = 2;
+    assert!(plain == Plain::new(2));
+    // `order` and `hash` stay off. Unlike Python's, `repr` emits nothing on
+    // its own: `Debug` and `Display` are left to a derive or to opting in.
+    assert!(implements!(Plain: PartialEq));
+    assert!(!implements!(Plain: PartialOrd));
+    assert!(!implements!(Plain: Hash));
+    assert!(!implements!(Plain: Debug));
+    assert!(!implements!(Plain: Display));
+
+    #[derive(Dataclass)]
+    #[dataclass(debug, display)]
+    struct Shown {
+        x: i32,
+    }
+    assert_eq!(
+        format!("{:?} {}", Shown::new(1), Shown::new(1)),
+        "Shown { x: 1 } Shown(x=1)"
+    );
+
+    #[derive(Dataclass)]
+    #[dataclass(repr = false, full)]
+    struct Quiet {
+        x: i32,
+    }
+    assert!(implements!(Quiet: PartialOrd));
+    assert!(implements!(Quiet: Hash));
+    assert!(!implements!(Quiet: Display));
+
+    #[derive(Dataclass)]
+    #[dataclass(kw_only)]
+    struct Named {
+        x: i32,
+        #[dataclass(default)]
+        y: i32,
+    }
+    let named = Named::builder().x(1).build().unwrap();
+    assert_eq!((named.x, named.y), (1, 0));
+}
+
+#[test]
+fn with_field_updates_one_field_by_name() {
+    #[derive(Dataclass, Debug, Clone)]
+    #[dataclass(with_field, rename_all = "camelCase")]
+    struct Profile {
+        display_name: String,
+        age: u32,
+        #[dataclass(skip, default)]
+        session: Option<u64>,
+    }
+
+    let profile = Profile::new("Ada".to_string(), 36);
+    let older = profile.with_field("age", Value::U32(37)).unwrap();
+    assert_eq!(older, Profile::new("Ada".to_string(), 37));
+    assert_eq!(profile.age, 36);
+
+    let renamed = profile
+        .with_field("displayName", Value::String("Grace".to_string()))
+        .unwrap();
+    assert_eq!(renamed.display_name, "Grace");
+
+    assert!(profile
+        .with_field("age", Value::String("old".to_string()))
+        .is_err());
+    assert_eq!(
+        profile
+            .with_field("session", Value::Option(None))
+            .unwrap_err(),
+        "no field named `session`"
+    );
+}
+
+#[test]
+fn ordered_float_dataclasses_sort_by_partial_cmp() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(order)]
+    struct Reading {
+        celsius: f64,
+        sensor: u8,
+    }
+
+    let mut readings = vec![
+        Reading::new(21.5, 2),
+        Reading::new(-3.0, 1),
+        Reading::new(21.5, 1),
+    ];
+    readings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        readings,
+        vec![
+            Reading::new(-3.0, 1),
+            Reading::new(21.5, 1),
+            Reading::new(21.5, 2),
+        ]
+    );
+    assert_eq!(
+        Reading::new(f64::NAN, 1).partial_cmp(&Reading::new(0.0, 1)),
+        None
+    );
+
+    // `partial_cmp` has no answer for NaN, so the caller picks one: here
+    // every NaN reading sorts after the numbers, keeping its original order.
+    let mut readings = [
+        Reading::new(f64::NAN, 4),
+        Reading::new(21.5, 2),
+        Reading::new(f64::NAN, 3),
+        Reading::new(-3.0, 1),
+    ];
+    readings.sort_by(|a, b| {
+        a.partial_cmp(b)
+            .unwrap_or_else(|| a.celsius.is_nan().cmp(&b.celsius.is_nan()))
+    });
+    let sensors: Vec<_> = readings.iter().map(|reading| reading.sensor).collect();
+    assert_eq!(sensors, [1, 2, 4, 3]);
+    assert!(readings[2].celsius.is_nan() && readings[3].celsius.is_nan());
+}
+
+#[test]
+fn take_moves_an_optional_field_out() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(option_helpers)]
+    struct Upload {
+        name: String,
+        #[dataclass(default)]
+        pending: Option<Vec<u8>>,
+    }
+
+    let mut upload = Upload::new("logo.png".to_string());
+    upload.set_pending(vec![1, 2, 3]);
+    assert_eq!(upload.take_pending(), Some(vec![1, 2, 3]));
+    assert_eq!(upload.pending, None);
+    assert_eq!(upload.take_pending(), None);
+}
+
+#[test]
+fn uncompared_fields_are_left_out_of_the_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[derive(Dataclass)]
+    #[dataclass(hash)]
+    struct Session {
+        id: u64,
+        #[dataclass(compare = false)]
+        last_seen: u64,
+    }
+
+    let first = Session::new(1, 10);
+    let second = Session::new(1, 20);
+    assert!(first == second);
+    assert_eq!(hash_of(&first), hash_of(&second));
+}
+
+#[test]
+fn with_reruns_the_freeze_hook_and_validators() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(frozen, with, freeze_after = "seal")]
+    struct Packet {
+        payload: Vec<u8>,
+        #[dataclass(default)]
+        checksum: u32,
+    }
+
+    impl Packet {
+        fn seal(&mut self) {
+            self.checksum = self.payload.iter().map(|&byte| u32::from(byte)).sum();
+        }
+    }
+
+    let packet = Packet::new(vec![1, 2, 3]).with_payload(vec![10, 20]);
+    assert_eq!(packet.checksum, 30);
+
+    fn not_blank(name: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            Err("name is blank".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(with)]
+    struct Tag {
+        #[dataclass(validate = "not_blank")]
+        name: String,
+    }
+
+    let tag = Tag::new("rust".to_string());
+    assert_eq!(tag.with_name(" ".to_string()).unwrap_err(), "name is blank");
+}
+
+#[test]
+fn bounded_values_run_the_freeze_hook() {
+    #[derive(Dataclass, Debug)]
+    #[dataclass(frozen, bounded, freeze_after = "total_up")]
+    struct Pair {
+        a: u8,
+        b: u8,
+        #[dataclass(default)]
+        total: u16,
+    }
+
+    impl Pair {
+        fn total_up(&mut self) {
+            self.total = u16::from(self.a) + u16::from(self.b);
+        }
+    }
+
+    assert_eq!(Pair::max_value().total, 510);
+    assert_eq!(Pair::min_value().total, 0);
+}
+
+#[test]
+fn total_order_adds_ord_for_btree_and_heap_use() {
+    use std::collections::{BTreeSet, BinaryHeap};
+
+    #[derive(Dataclass, Debug, Eq)]
+    #[dataclass(order(total))]
+    struct Job {
+        #[dataclass(order_desc)]
+        priority: u8,
+        id: u32,
+    }
+
+    let mut jobs = vec![Job::new(1, 7), Job::new(3, 2), Job::new(1, 4)];
+    jobs.sort();
+    assert_eq!(jobs, [Job::new(3, 2), Job::new(1, 4), Job::new(1, 7)]);
+
+    let set: BTreeSet<_> = jobs.into_iter().collect();
+    assert_eq!(set.iter().next(), Some(&Job::new(3, 2)));
+
+    let mut heap: BinaryHeap<_> = vec![Job::new(2, 1), Job::new(5, 1)].into_iter().collect();
+    assert_eq!(heap.pop(), Some(Job::new(2, 1)));
+}
+
+#[test]
+fn plain_order_only_needs_partial_ord_fields() {
+    type Score = f64;
+
+    #[derive(Debug, PartialEq, PartialOrd)]
+    struct Meters(f64);
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(order)]
+    struct Run {
+        score: Score,
+        distance: Meters,
+    }
+
+    assert!(Run::new(1.0, Meters(5.0)) < Run::new(2.0, Meters(0.0)));
+    assert!(Run::new(1.0, Meters(5.0)) > Run::new(1.0, Meters(4.5)));
+}
+
+#[test]
+fn total_float_eq_orders_floats_by_total_cmp() {
+    use std::cmp::Ordering;
+    use std::collections::BTreeSet;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(order(total), total_float_eq)]
+    struct Sample {
+        value: f64,
+        id: u8,
+    }
+
+    let nan = Sample::new(f64::NAN, 1);
+    assert_eq!(nan, Sample::new(f64::NAN, 1));
+    assert_eq!(
+        nan.partial_cmp(&Sample::new(f64::NAN, 1)),
+        Some(Ordering::Equal)
+    );
+    assert_ne!(Sample::new(0.0, 1), Sample::new(-0.0, 1));
+    assert_eq!(
+        Sample::new(-0.0, 1).partial_cmp(&Sample::new(0.0, 1)),
+        Some(Ordering::Less)
+    );
+
+    let set: BTreeSet<_> = vec![nan, Sample::new(1.0, 2), Sample::new(-1.0, 3)]
+        .into_iter()
+        .collect();
+    let ids: Vec<_> = set.iter().map(|sample| sample.id).collect();
+    assert_eq!(ids, [3, 2, 1]);
+}
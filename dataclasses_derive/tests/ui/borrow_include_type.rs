@@ -0,0 +1,7 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(borrow, hash(include_type))]
+struct UserName(String);
+
+fn main() {}
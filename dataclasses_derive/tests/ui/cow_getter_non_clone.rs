@@ -0,0 +1,12 @@
+use dataclasses_derive::Dataclass;
+
+struct Handle;
+
+#[derive(Dataclass)]
+#[dataclass(accessors, eq = false)]
+struct Pool {
+    #[dataclass(cow_getter)]
+    handles: Vec<Handle>,
+}
+
+fn main() {}
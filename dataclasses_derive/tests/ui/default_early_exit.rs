@@ -0,0 +1,9 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+struct Config {
+    #[dataclass(default = "\"8080\".parse()?")]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use dataclasses_derive::Dataclass;
+use std::num::NonZeroU32;
+
+#[derive(Dataclass)]
+struct Pool {
+    #[dataclass(default)]
+    workers: NonZeroU32,
+}
+
+fn main() {}
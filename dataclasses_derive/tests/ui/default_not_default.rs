@@ -0,0 +1,13 @@
+use dataclasses_derive::Dataclass;
+
+struct Opaque;
+
+#[derive(Dataclass)]
+#[dataclass(eq = false)]
+struct Holder {
+    id: u32,
+    #[dataclass(default)]
+    handle: Opaque,
+}
+
+fn main() {}
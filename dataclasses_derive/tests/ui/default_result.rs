@@ -0,0 +1,9 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+struct Job {
+    #[dataclass(default)]
+    status: Result<i32, String>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use dataclasses_derive::Dataclass;
+
+fn default_host() -> &'static str {
+    "localhost"
+}
+
+#[derive(Dataclass)]
+struct Server {
+    #[dataclass(default_factory = "default_host")]
+    host: String,
+    #[dataclass(default = "\"8080\"")]
+    port: u16,
+}
+
+fn main() {}
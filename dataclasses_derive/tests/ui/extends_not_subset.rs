@@ -0,0 +1,18 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+struct Entity {
+    id: u64,
+    version: u32,
+}
+
+#[derive(Dataclass)]
+#[dataclass(extends = "Entity")]
+struct Invoice {
+    #[dataclass(base)]
+    id: u64,
+    #[dataclass(base)]
+    total: u32,
+}
+
+fn main() {}
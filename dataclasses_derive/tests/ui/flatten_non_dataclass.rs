@@ -0,0 +1,14 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(serde::Serialize, PartialEq)]
+struct Tags;
+
+#[derive(Dataclass)]
+#[dataclass(as_dict)]
+struct Customer {
+    name: String,
+    #[dataclass(flatten)]
+    tags: Tags,
+}
+
+fn main() {}
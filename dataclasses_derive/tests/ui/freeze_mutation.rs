@@ -0,0 +1,19 @@
+mod geometry {
+    use dataclasses_derive::Dataclass;
+
+    #[derive(Dataclass, Debug)]
+    #[dataclass(freeze)]
+    pub struct Point {
+        x: i32,
+        y: i32,
+    }
+}
+
+use geometry::Point;
+
+fn main() {
+    let mut point = Point::new(1, 2);
+    point.x = 3;
+    *point.y_mut() = 4;
+    println!("{}", point.x());
+}
@@ -0,0 +1,12 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(frozen, dynamic)]
+struct Limits {
+    max: u32,
+}
+
+fn main() {
+    let mut limits = Limits::new(10);
+    limits.for_each_field_mut(|_, _| {});
+}
@@ -0,0 +1,11 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(hash_by(id, last_seen))]
+struct Session {
+    id: u64,
+    #[dataclass(compare = false)]
+    last_seen: u64,
+}
+
+fn main() {}
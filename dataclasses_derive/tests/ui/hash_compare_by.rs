@@ -0,0 +1,16 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(hash, compare_by = "key")]
+struct Tag {
+    name: String,
+    label: String,
+}
+
+impl Tag {
+    fn key(&self) -> String {
+        self.name.to_lowercase()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(hash)]
+struct Session {
+    id: u64,
+    #[dataclass(compare = false, hash = true)]
+    last_seen: u64,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(order(total))]
+struct Reading {
+    celsius: f64,
+}
+
+impl Eq for Reading {}
+
+fn main() {}
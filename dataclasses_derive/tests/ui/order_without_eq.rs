@@ -0,0 +1,10 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+#[dataclass(order, eq = false)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+mod accounts {
+    use dataclasses_derive::Dataclass;
+
+    #[derive(Dataclass)]
+    #[dataclass(builder, private_new)]
+    pub struct Account {
+        email: String,
+    }
+}
+
+fn main() {
+    let _ = accounts::Account::new("ada".to_string());
+}
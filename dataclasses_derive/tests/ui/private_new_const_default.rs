@@ -0,0 +1,18 @@
+use dataclasses_derive::Dataclass;
+
+fn check_email(email: &String) -> Result<(), String> {
+    if email.contains('@') {
+        Ok(())
+    } else {
+        Err(format!("invalid email `{}`", email))
+    }
+}
+
+#[derive(Dataclass)]
+#[dataclass(builder, private_new, const_default)]
+pub struct Account {
+    #[dataclass(validate = "check_email")]
+    email: String,
+}
+
+fn main() {}
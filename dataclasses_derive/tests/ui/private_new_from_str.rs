@@ -0,0 +1,18 @@
+use dataclasses_derive::Dataclass;
+
+fn check_email(email: &String) -> Result<(), String> {
+    if email.contains('@') {
+        Ok(())
+    } else {
+        Err(format!("invalid email `{}`", email))
+    }
+}
+
+#[derive(Dataclass)]
+#[dataclass(builder, private_new, from_str)]
+pub struct Email {
+    #[dataclass(validate = "check_email")]
+    address: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass)]
+struct Config {
+    #[dataclass(defualt)]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use dataclasses_derive::Dataclass;
+
+#[derive(Dataclass, Clone, serde::Deserialize)]
+#[dataclass(with, with_field)]
+pub struct Form {
+    name: String,
+    field: String,
+}
+
+fn main() {}